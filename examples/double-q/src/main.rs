@@ -25,7 +25,6 @@ extern crate pest;
 #[macro_use]
 extern crate pest_derive;
 
-use std::mem;
 use std::time::SystemTime;
 use structopt::StructOpt;
 //use std::{thread, time};
@@ -63,23 +62,23 @@ fn main() {
     
     for i in 0..opt.facts {
         start += 1;
-        let s = sets[(i % nsets) as usize];
+        let s = sets[i % nsets];
         let name = format!("{}{}{}", s, i, start);
         let f = Box::leak(Box::new(format!("{name} ISA{start} {s} ◊", name = name, start = start, s = s)));
-        db.tell( unsafe { mem::transmute( f.as_str() ) });
+        db.tell(f.as_str());
 
         if (i % opt.report) == 0 {
             let t1 = SystemTime::now();
             start += 1;
             let f = Box::leak(Box::new(format!("susan ISA{start} person ◊ johnny ISA{start} person ◊", start = start)));
-            db.tell( unsafe { mem::transmute( f.as_str() ) });
+            db.tell(f.as_str());
             let t2 = SystemTime::now();
 
             let t_f = t2.duration_since(t1).unwrap().as_micros() as f64 / 2.0;
 
             let f = Box::leak(Box::new(format!("johnny ISA{start} <X1> ◊ susan ISA{start} <X1> ◊", start = start)));
-            let resp = db.ask( unsafe { mem::transmute( f.as_str() ) });
-            if resp.len() == 0 {
+            let resp = db.ask(f.as_str());
+            if resp.is_empty() {
                 println!("Wrong resp for {}", f);
             }
             let t3 = SystemTime::now();
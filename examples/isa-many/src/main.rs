@@ -25,7 +25,6 @@ extern crate pest;
 #[macro_use]
 extern crate pest_derive;
 
-use std::mem;
 use std::time::SystemTime;
 use structopt::StructOpt;
 //use std::{thread, time};
@@ -67,26 +66,26 @@ fn main() {
     
     for i in 0..opt.facts {
         start += 1;
-        let s = sets[(i % nsets) as usize];
+        let s = sets[i % nsets];
         let name = format!("{}{}{}", s, i, start);
         let f = format!("{name} ISA{start} {s} ◊", name = name, start = start, s = s);
-        db.tell( unsafe { mem::transmute( f.as_str() ) });
+        db.tell(f.as_str());
 
         if (i % opt.report) == 0 {
             let t1 = SystemTime::now();
             start += 1;
             for n in 0..opt.answers {
-                let s = sets[(n % nsets) as usize];
+                let s = sets[n % nsets];
                 let name = format!("{}{}{}", s, n, start);
                 let f = format!("john ISA{start} {name} ◊", name = name, start = start);
-                db.tell( unsafe { mem::transmute( f.as_str() ) });
+                db.tell(f.as_str());
             }
             let t2 = SystemTime::now();
 
             let t_f = t2.duration_since(t1).unwrap().as_micros() as f64 / opt.answers as f64;
 
             let f = format!("john ISA{start} X1 ◊", start = start);
-            let resp = db.ask( unsafe { mem::transmute( f.as_str() ) });
+            let resp = db.ask(f.as_str());
             if resp.len() != opt.answers {
                 println!("Wrong resp for {}: found {}, expected {}", f, resp.len(), opt.answers);
             }
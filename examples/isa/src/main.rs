@@ -25,7 +25,6 @@ extern crate pest;
 #[macro_use]
 extern crate pest_derive;
 
-use std::mem;
 use std::time::SystemTime;
 use structopt::StructOpt;
 //use std::{thread, time};
@@ -64,10 +63,10 @@ fn main() {
     
     for i in 0..opt.facts {
         start += 1;
-        let s = sets[(i % nsets) as usize];
+        let s = sets[i % nsets];
         let f = format!("{s}{i}{start} ISA{start} {s} ◊", s=s, i=i, start = start);
         {
-            db.tell( unsafe { mem::transmute( f.as_str() ) });
+            db.tell(f.as_str());
         }
         count += 1;
 
@@ -75,9 +74,9 @@ fn main() {
             let t1 = SystemTime::now();
             start += 1;
             for n in 0..10 {
-                let s = sets[(n % nsets) as usize];
+                let s = sets[n % nsets];
                 let f = format!("{s}{n}{start} ISA{start} {s} ◊", s=s, n=n, start = start);
-                db.tell( unsafe { mem::transmute( f.as_str() ) });
+                db.tell(f.as_str());
                 count += 1;
             }
             let t2 = SystemTime::now();
@@ -85,10 +84,10 @@ fn main() {
             let t_f = t2.duration_since(t1).unwrap().as_micros() as f64 / 10.0;
 
             for n in 0..10 {
-                let s = sets[(n % nsets) as usize];
+                let s = sets[n % nsets];
                 let f = format!("{s}{n}{start} ISA{start} {s} ◊", s=s, n=n, start = start);
-                let resp = db.ask( unsafe { mem::transmute( f.as_str() ) });
-                if resp.len() == 0 {
+                let resp = db.ask(f.as_str());
+                if resp.is_empty() {
                     println!("Wrong resp for {}", f);
                 }
             }
@@ -25,13 +25,10 @@ extern crate pest;
 #[macro_use]
 extern crate pest_derive;
 
-use std::mem;
 use std::time::SystemTime;
 use structopt::StructOpt;
 //use std::{thread, time};
 
-use rand;
-
 use crate::uindex::kbase::DBGen;
 use crate::uindex::kbase::DataBase;
 
@@ -116,19 +113,19 @@ fn main() {
     
     for i in 0..opt.facts {
         let f = make_tree(opt.treedepth, opt.branchlength);
-        db.tell( unsafe { mem::transmute( f.as_str() ) });
+        db.tell(f.as_str());
         count += 1;
 
         if (i % opt.report) == 0 {
             let q = make_tree_full(opt.treedepth, opt.branchlength);
             let t1 = SystemTime::now();
-            db.tell( unsafe { mem::transmute( q.as_str() ) });
+            db.tell(q.as_str());
             count += 1;
             let t2 = SystemTime::now();
 
             let t_f = t2.duration_since(t1).unwrap().as_nanos() as f64 / 1000.0;
 
-            let resp = db.ask( unsafe { mem::transmute( q.as_str() ) });
+            let resp = db.ask(q.as_str());
             if resp.len() != 1 {
                 println!("Wrong resp for {}: found {}, expected {}", f, resp.len(), 1);
             }
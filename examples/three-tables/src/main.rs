@@ -25,7 +25,6 @@ extern crate pest;
 #[macro_use]
 extern crate pest_derive;
 
-use std::mem;
 use std::time::SystemTime;
 use structopt::StructOpt;
 //use std::{thread, time};
@@ -76,8 +75,8 @@ fn main() {
 
         let t1 = SystemTime::now();
 
-        db.tell( unsafe { mem::transmute( f1.as_str() ) });
-        db.tell( unsafe { mem::transmute( f2.as_str() ) });
+        db.tell(f1.as_str());
+        db.tell(f2.as_str());
         count += 2;
 
         if i < 100 {
@@ -85,7 +84,7 @@ fn main() {
             let country = format!("country{}", start % 50);
 
             let f3 = format!("T {} {} {} ◊", city, population, country);
-            db.tell( unsafe { mem::transmute( f3.as_str() ) });
+            db.tell(f3.as_str());
             count += 1;
         }
         
@@ -93,18 +92,18 @@ fn main() {
 
         if (i % opt.report) == 0 {
 
-            let t_f = t2.duration_since(t1).unwrap().as_nanos() as f64 / 1000.0 as f64;
+            let t_f = t2.duration_since(t1).unwrap().as_nanos() as f64 / 1000.0;
 
             let f = format!("U {} {} X1 ◊ A X1 X2 X3 X4 ◊ T X4 X5 X6 ◊", given_name, surname);
 
-            let resp = db.ask( unsafe { mem::transmute( f.as_str() ) });
+            let resp = db.ask(f.as_str());
 
             if resp.len() != 1 {
                 println!("Wrong resp for {}: found {:?}", f, resp);
             }
             let t3 = SystemTime::now();
 
-            let t_q = t3.duration_since(t2).unwrap().as_nanos() as f64 / 1000.0 as f64;
+            let t_q = t3.duration_since(t2).unwrap().as_nanos() as f64 / 1000.0;
 
             println!("{:.3}  {:.3}", t_f, t_q);
         }
@@ -33,6 +33,7 @@
 import sqlite3
 from typing import Any, Dict, List, Union
 import argparse
+import os
 import time
 
 DB_SCHEMA = """
@@ -50,6 +51,8 @@ CREATE INDEX IF NOT EXISTS [objIx] ON [Facts] ([objt]);
 INSERT = "INSERT INTO Facts (subj, verb, objt) VALUES (?, ?, ?);"
 QUERY = "SELECT f1.objt FROM Facts as f1, Facts as f2 WHERE f1.subj = ? and f1.verb = ? and f1.objt = f2.objt and f2.subj = ? and f2.verb = ?;"
 
+GRAMMAR = os.path.join(os.path.dirname(__file__), '..', 'examples', 'double-q', 'src', 'fact.pest')
+
 SETS = ["thing", "animal", "mammal", "primate", "human"]
 NSETS = 5
 
@@ -58,6 +61,8 @@ parser.add_argument('-f', dest='f', type=int, default=2,
                     help='number of facts to add')
 parser.add_argument('-r', dest='r', type=int, default=1,
                     help='report every r facts')
+parser.add_argument('-u', dest='u', action='store_true',
+                    help='use uindex instead of SQLite')
 
 
 def make_dicts(cursor, row):
@@ -117,11 +122,31 @@ class SqliteBench():
         return self._db_query(QUERY, (subj1, verb1, subj2, verb2))
 
 
+class UindexBench():
+    """
+    Needs the uindex Python bindings, from uindex_py.
+    """
+    def __init__(self, grammar_path=GRAMMAR):
+        import uindex
+        with open(grammar_path) as grammar:
+            self.db = uindex.DB(grammar.read())
+
+    def tell(self, subj, verb, objt):
+        """
+        """
+        self.db.tell(f"{subj} {verb} {objt} ◊")
+
+    def ask(self, subj1, verb1, subj2, verb2):
+        """
+        """
+        return self.db.ask(f"{subj1} {verb1} <X1> ◊ {subj2} {verb2} <X1> ◊")
+
+
 if __name__ == '__main__':
     args = parser.parse_args()
     t0 = time.time()
     start = 0
-    sqdb = SqliteBench()
+    sqdb = UindexBench() if args.u else SqliteBench()
 
     for i in range(args.f):
 
@@ -33,6 +33,7 @@
 import sqlite3
 from typing import Any, Dict, List, Union
 import argparse
+import os
 import time
 
 DB_SCHEMA = """
@@ -49,6 +50,8 @@ CREATE INDEX IF NOT EXISTS [subjIx] ON [Facts] ([subj], [verb], [objt]);
 INSERT = "INSERT INTO Facts (subj, verb, objt) VALUES (?, ?, ?);"
 QUERY = "SELECT fact_id FROM Facts WHERE subj = ? and verb = ?;"
 
+GRAMMAR = os.path.join(os.path.dirname(__file__), '..', 'examples', 'isa-many', 'src', 'fact.pest')
+
 SETS = ["thing", "animal", "mammal", "primate", "human"]
 NSETS = 5
 
@@ -59,6 +62,8 @@ parser.add_argument('-r', dest='r', type=int, default=1,
                     help='report every r facts')
 parser.add_argument('-a', dest='a', type=int, default=1,
                     help='prepare a answers for each question')
+parser.add_argument('-u', dest='u', action='store_true',
+                    help='use uindex instead of SQLite')
 
 
 def make_dicts(cursor, row):
@@ -118,11 +123,31 @@ class SqliteBench():
         return self._db_query(QUERY, (subj, verb))
 
 
+class UindexBench():
+    """
+    Needs the uindex Python bindings, from uindex_py.
+    """
+    def __init__(self, grammar_path=GRAMMAR):
+        import uindex
+        with open(grammar_path) as grammar:
+            self.db = uindex.DB(grammar.read())
+
+    def tell(self, subj, verb, objt):
+        """
+        """
+        self.db.tell(f"{subj} {verb} {objt} ◊")
+
+    def ask(self, subj, verb):
+        """
+        """
+        return self.db.ask(f"{subj} {verb} X1 ◊")
+
+
 if __name__ == '__main__':
     args = parser.parse_args()
     t0 = time.time()
     start = 0
-    sqdb = SqliteBench()
+    sqdb = UindexBench() if args.u else SqliteBench()
 
     for i in range(args.f):
 
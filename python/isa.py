@@ -33,6 +33,7 @@
 import sqlite3
 from typing import Any, Dict, List, Union
 import argparse
+import os
 import time
 
 DB_SCHEMA = """
@@ -49,6 +50,8 @@ CREATE INDEX IF NOT EXISTS [subjIx] ON [Facts] ([subj], [verb], [objt]);
 INSERT = "INSERT INTO Facts (subj, verb, objt) VALUES (?, ?, ?);"
 QUERY = "SELECT fact_id FROM Facts WHERE subj = ? and verb = ? and objt = ?;"
 
+GRAMMAR = os.path.join(os.path.dirname(__file__), '..', 'examples', 'isa', 'src', 'fact.pest')
+
 SETS = ["thing", "animal", "mammal", "primate", "human"]
 NSETS = 5
 
@@ -57,6 +60,8 @@ parser.add_argument('-f', dest='f', type=int, default=2,
                     help='number of facts to add')
 parser.add_argument('-r', dest='r', type=int, default=1,
                     help='report every r facts')
+parser.add_argument('-u', dest='u', action='store_true',
+                    help='use uindex instead of SQLite')
 
 
 def make_dicts(cursor, row):
@@ -116,11 +121,31 @@ class SqliteBench():
         return self._db_query(QUERY, (subj, verb, objt))
 
 
+class UindexBench():
+    """
+    Needs the uindex Python bindings, from uindex_py.
+    """
+    def __init__(self, grammar_path=GRAMMAR):
+        import uindex
+        with open(grammar_path) as grammar:
+            self.db = uindex.DB(grammar.read())
+
+    def tell(self, subj, verb, objt):
+        """
+        """
+        self.db.tell(f"{subj} {verb} {objt} ◊")
+
+    def ask(self, subj, verb, objt):
+        """
+        """
+        return self.db.ask(f"{subj} {verb} {objt} ◊")
+
+
 if __name__ == '__main__':
     args = parser.parse_args()
     t0 = time.time()
     start = 0
-    sqdb = SqliteBench()
+    sqdb = UindexBench() if args.u else SqliteBench()
 
     for i in range(args.f):
 
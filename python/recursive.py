@@ -33,6 +33,7 @@
 import sqlite3
 from typing import Any, Dict, List, Union
 import argparse
+import os
 import random
 import time
 
@@ -83,6 +84,8 @@ INSERT_LCHILD = "INSERT INTO Child (parent_branch, idx, is_branch, leaf) VALUES
 QUERY_BCHILD = "SELECT child_id FROM Child WHERE parent_branch = ? AND idx = ? AND is_branch = 1 AND branch = ?;"
 QUERY_LCHILD = "SELECT child_id FROM Child WHERE parent_branch = ? AND idx = ? AND is_branch = 0 AND leaf = ?;"
 
+GRAMMAR = os.path.join(os.path.dirname(__file__), '..', 'examples', 'recursive', 'src', 'fact.pest')
+
 
 parser = argparse.ArgumentParser(description='Benchmark on ont.')
 parser.add_argument('-f', dest='f', type=int, default=2,
@@ -93,6 +96,8 @@ parser.add_argument('-d', dest='d', type=int, default=1,
                     help='max depth of tree')
 parser.add_argument('-l', dest='l', type=int, default=1,
                     help='max length of branches')
+parser.add_argument('-u', dest='u', action='store_true',
+                    help='use uindex instead of SQLite')
 
 
 def make_dicts(cursor, row):
@@ -140,6 +145,49 @@ class SqliteBench():
     def commit(self):
         self.db.commit()
 
+    def tell_tree(self, depth, length):
+        make_tree(self, depth, length)
+
+    def tell_query(self, depth, length):
+        return make_tree_q(self, depth, length)
+
+    def ask(self, q):
+        return self.query(q, ())
+
+
+class UindexBench():
+    """
+    Needs the uindex Python bindings, from uindex_py.
+    """
+    def __init__(self, grammar_path=GRAMMAR):
+        import uindex
+        with open(grammar_path) as grammar:
+            self.db = uindex.DB(grammar.read())
+
+    def tell_tree(self, depth, length):
+        self.db.tell(f"{make_text_tree(depth, depth, length, False)} ◊")
+
+    def tell_query(self, depth, length):
+        q = f"{make_text_tree(depth, depth, length, True)} ◊"
+        self.db.tell(q)
+        return q
+
+    def ask(self, q):
+        return self.db.ask(q)
+
+
+def make_text_tree(depth, max_depth, length, full):
+    """
+    The text of a random tree, with as many branches as do_make_tree_full
+    if full, and as do_make_tree otherwise, though always a branch at the root.
+    """
+    name = random.randrange(10 ** (max_depth - depth + 1))
+    if depth == 0 or (not full and depth != max_depth and random.randrange(8) > 5):
+        return f"{name}"
+    children = length if full else random.randrange(length)
+    subtrees = [make_text_tree(depth - 1, max_depth, length, full) for _ in range(children)]
+    return f"({' '.join([str(name)] + subtrees)})"
+
 
 def make_tree(db, depth, length):
     do_make_tree(db, depth, depth, length)
@@ -273,22 +321,22 @@ def do_make_tree_q(db, depth, max_depth, length, select, join, where):
 if __name__ == '__main__':
     args = parser.parse_args()
     t0 = time.time()
-    sqdb = SqliteBench()
+    sqdb = UindexBench() if args.u else SqliteBench()
 
     for i in range(args.f):
 
-        make_tree(sqdb, args.d, args.l)
+        sqdb.tell_tree(args.d, args.l)
 
         if (i % args.r) == 0:
             t1 = time.time()
 
-            q = make_tree_q(sqdb, args.d, args.l)
+            q = sqdb.tell_query(args.d, args.l)
 
             t2 = time.time()
 
             t_f = (t2 - t1) * 1000000.0
 
-            resp = sqdb.query(q, ())
+            resp = sqdb.ask(q)
             if len(resp) != 1:
                 print(f"Wrong resp for {q}, expected 1, found {resp}")
 
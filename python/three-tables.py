@@ -33,6 +33,7 @@
 import sqlite3
 from typing import Any, Dict, List, Union
 import argparse
+import os
 import time
 
 DB_SCHEMA = """
@@ -81,6 +82,8 @@ QUERY_USER = "SELECT user_id FROM User WHERE userid = ? AND given_name = ? AND s
 QUERY_CITY = "SELECT city_id FROM City WHERE name = ? AND population = ? AND country = ?;"
 QUERY_CITY_BY_NAME = "SELECT city_id FROM City WHERE name = ?;"
 
+GRAMMAR = os.path.join(os.path.dirname(__file__), '..', 'examples', 'three-tables', 'src', 'fact.pest')
+
 QUERY = "SELECT c.population, c.country FROM User as u JOIN Address as a ON a.user = u.user_id JOIN City as c ON c.city_id = a.city WHERE u.given_name = ? AND u.surname = ?;"
 
 
@@ -89,6 +92,8 @@ parser.add_argument('-f', dest='f', type=int, default=2,
                     help='number of facts to add')
 parser.add_argument('-r', dest='r', type=int, default=1,
                     help='report every r facts')
+parser.add_argument('-u', dest='u', action='store_true',
+                    help='use uindex instead of SQLite')
 
 
 def make_dicts(cursor, row):
@@ -175,11 +180,64 @@ class SqliteBench():
         return self._db_query(QUERY, (given_name, surname), one=True)
 
 
+class UindexBench():
+    """
+    Needs the uindex Python bindings, from uindex_py.
+    Users and cities are referred to by their userid and name.
+    """
+    def __init__(self, grammar_path=GRAMMAR):
+        import uindex
+        with open(grammar_path) as grammar:
+            self.db = uindex.DB(grammar.read())
+
+    def tell_user(self, userid, given_name, surname):
+        """
+        """
+        self.db.tell(f"U {given_name} {surname} {userid} ◊")
+
+    def tell_city(self, name, population, country):
+        """
+        """
+        self.db.tell(f"T {name} {population} {country} ◊")
+
+    def tell_address(self, user, street, number, city):
+        """
+        """
+        self.db.tell(f"A {user} {street} {number} {city} ◊")
+
+    def query_user(self, userid, given_name, surname):
+        """
+        """
+        if not self.db.ask(f"U {given_name} {surname} {userid} ◊"):
+            raise LookupError(userid)
+        return userid
+
+    def query_city(self, name, population, country):
+        """
+        """
+        if not self.db.ask(f"T {name} {population} {country} ◊"):
+            raise LookupError(name)
+        return name
+
+    def query_city_by_name(self, name):
+        """
+        """
+        if not self.db.ask(f"T {name} X1 X2 ◊"):
+            raise LookupError(name)
+        return name
+
+    def query(self, given_name, surname):
+        """
+        """
+        answers = self.db.ask(f"U {given_name} {surname} X1 ◊ A X1 X2 X3 X4 ◊ T X4 X5 X6 ◊")
+        return answers[0] if answers else {}
+
+
 if __name__ == '__main__':
     args = parser.parse_args()
     t0 = time.time()
     start = 0
-    db = SqliteBench()
+    db = UindexBench() if args.u else SqliteBench()
 
     for i in range(args.f):
 
@@ -0,0 +1,218 @@
+// Copyright (c) 2020 by Enrique Pérez Arnaud <enrique at cazalla.net>
+//
+// This file is part of the modus_ponens project.
+// http://www.modus_ponens.net
+//
+// The modus_ponens project is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// The modus_ponens project is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with any part of the modus_ponens project.
+// If not, see <http://www.gnu.org/licenses/>.
+
+//! Answers to queries, as the values bound to the variables in them.
+
+use std::collections::HashMap;
+use std::slice;
+
+use pest::iterators::Pair;
+use pest::RuleType;
+
+use crate::lexicon::Lexicon;
+use crate::matching::MPMatching;
+use crate::segment::MPSegment;
+use crate::sentence::RuleName;
+
+/// The value bound to a variable, along with the name of its production and,
+/// for non terminal productions, the structure of the value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Binding {
+    pub var: String,
+    pub value: String,
+    pub production: String,
+    pub tree: Option<Tree>,
+}
+
+/// The parse tree of some text, with empty nodes left out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Tree {
+    pub production: String,
+    pub text: String,
+    pub children: Vec<Tree>,
+}
+
+impl Tree {
+    pub fn from_pair<R: RuleType + RuleName>(pair: Pair<R>) -> Tree {
+        Tree {
+            production: pair.as_rule().rule_name(),
+            text: pair.as_str().to_string(),
+            children: pair
+                .into_inner()
+                .filter(|child| !child.as_str().trim().is_empty())
+                .map(Tree::from_pair)
+                .collect(),
+        }
+    }
+}
+
+/// The values bound to the variables of a query in one of its answers,
+/// in the order in which the variables first appear in the query.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Bindings {
+    bindings: Vec<Binding>,
+}
+
+impl Bindings {
+    /// The bindings in a matching, for the given variables.
+    pub fn new(lexicon: &Lexicon, vars: &[&MPSegment], matching: &MPMatching) -> Bindings {
+        let bindings = vars
+            .iter()
+            .filter_map(|var| {
+                let value = matching.get(var)?;
+                Some(Binding {
+                    var: var.text.clone(),
+                    value: value.text.clone(),
+                    production: lexicon
+                        .rule_name(value.name)
+                        .unwrap_or_default()
+                        .to_string(),
+                    tree: None,
+                })
+            })
+            .collect();
+        Bindings { bindings }
+    }
+    /// The value bound to the variable.
+    pub fn get(&self, var: &str) -> Option<&str> {
+        self.binding(var).map(|binding| binding.value.as_str())
+    }
+    /// The name of the production of the value bound to the variable.
+    pub fn production(&self, var: &str) -> Option<&str> {
+        self.binding(var).map(|binding| binding.production.as_str())
+    }
+    pub fn binding(&self, var: &str) -> Option<&Binding> {
+        self.bindings.iter().find(|binding| binding.var == var)
+    }
+    /// Sets the structure of the value bound to the variable.
+    pub fn set_tree(&mut self, var: &str, tree: Tree) {
+        if let Some(binding) = self.bindings.iter_mut().find(|binding| binding.var == var) {
+            binding.tree = Some(tree);
+        }
+    }
+    pub fn iter(&self) -> slice::Iter<'_, Binding> {
+        self.bindings.iter()
+    }
+    pub fn len(&self) -> usize {
+        self.bindings.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.bindings.is_empty()
+    }
+    pub fn into_map(self) -> HashMap<String, String> {
+        self.bindings
+            .into_iter()
+            .map(|binding| (binding.var, binding.value))
+            .collect()
+    }
+}
+
+impl<'b> IntoIterator for &'b Bindings {
+    type Item = &'b Binding;
+    type IntoIter = slice::Iter<'b, Binding>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.bindings.iter()
+    }
+}
+
+impl IntoIterator for Bindings {
+    type Item = Binding;
+    type IntoIter = std::vec::IntoIter<Binding>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.bindings.into_iter()
+    }
+}
+
+impl From<Bindings> for HashMap<String, String> {
+    fn from(bindings: Bindings) -> Self {
+        bindings.into_map()
+    }
+}
+
+/// Bindings are serialized as a map from the text of each variable to either
+/// the text of its value or, for non terminal values, their structure.
+#[cfg(feature = "serde")]
+mod serialization {
+    use serde::de::{Deserialize, Deserializer, MapAccess, Visitor};
+    use serde::ser::{Serialize, SerializeMap, Serializer};
+    use std::fmt;
+
+    use super::{Binding, Bindings, Tree};
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(untagged)]
+    enum Value {
+        Text(String),
+        Tree(Tree),
+    }
+
+    impl Serialize for Bindings {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut map = serializer.serialize_map(Some(self.len()))?;
+            for binding in self {
+                match &binding.tree {
+                    Some(tree) => map.serialize_entry(&binding.var, tree)?,
+                    None => map.serialize_entry(&binding.var, &binding.value)?,
+                }
+            }
+            map.end()
+        }
+    }
+
+    struct BindingsVisitor;
+
+    impl<'de> Visitor<'de> for BindingsVisitor {
+        type Value = Bindings;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a map from variables to their values")
+        }
+
+        fn visit_map<M: MapAccess<'de>>(self, mut access: M) -> Result<Bindings, M::Error> {
+            let mut bindings = vec![];
+            while let Some((var, value)) = access.next_entry::<String, Value>()? {
+                bindings.push(match value {
+                    Value::Text(value) => Binding {
+                        var,
+                        value,
+                        production: String::new(),
+                        tree: None,
+                    },
+                    Value::Tree(tree) => Binding {
+                        var,
+                        value: tree.text.clone(),
+                        production: tree.production.clone(),
+                        tree: Some(tree),
+                    },
+                });
+            }
+            Ok(Bindings { bindings })
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Bindings {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Bindings, D::Error> {
+            deserializer.deserialize_map(BindingsVisitor)
+        }
+    }
+}
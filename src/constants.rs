@@ -23,3 +23,5 @@ pub const UNIQUE_PREFIX: &str = "v_u_";
 pub const NODE_MAP_CAPACITY: usize = 3;
 
 pub const NEW_RULES: bool = false;
+
+pub const READ_CHUNK_SIZE: usize = 1 << 16;
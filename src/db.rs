@@ -0,0 +1,627 @@
+// Copyright (c) 2020 by Enrique Pérez Arnaud <enrique at cazalla.net>
+//
+// This file is part of the modus_ponens project.
+// http://www.modus_ponens.net
+//
+// The modus_ponens project is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// The modus_ponens project is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with any part of the modus_ponens project.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use std::cell::{OnceCell, RefCell};
+use std::fs::File;
+use std::io;
+use std::mem;
+use std::path::Path;
+use std::str;
+
+use log::info;
+use pest::error::{Error, ErrorVariant};
+use pest::Position;
+
+use crate::bindings::{Bindings, Tree};
+use crate::constants::READ_CHUNK_SIZE;
+use crate::facttree::{Addition, FSNode, FactSet};
+use crate::hashing::MPHashMap;
+use crate::kbase::{DataBase, FactId, Stats, TellStatus, Told};
+use crate::matching::{binding, MPMatching};
+use crate::parse_result::ParseResult;
+use crate::path::MPPath;
+use crate::schema::Schema;
+use crate::sentence::{RuleName, SentenceParser};
+use crate::stream::{ImportReport, LineColumn, Progress, Skipped, TellError};
+use crate::typed::Sentence;
+
+/// A db holding the sentences that its parser can parse.
+pub struct MPDB<'a, P> {
+    mpparser: P,
+    facts: FactSet<'a>,
+    /// The last node of each sentence in the db, by id. The text of the
+    /// sentences is not kept, but built from the nodes along the way to them.
+    sentences: RefCell<MPHashMap<FactId, &'a FSNode<'a>>>,
+    /// How many sentences there are in the db, by the lexicon id
+    /// of the production at the top of their parse trees.
+    productions: RefCell<MPHashMap<u64, usize>>,
+    schema: OnceCell<Schema>,
+}
+
+impl<'a, P: SentenceParser<'a>> MPDB<'a, P> {
+    pub fn new(mpparser: P) -> MPDB<'a, P> {
+        let hasher = mpparser.lexicon().hasher();
+        MPDB {
+            mpparser,
+            facts: FactSet::with_hasher(hasher),
+            sentences: RefCell::new(MPHashMap::with_hasher(hasher)),
+            productions: RefCell::new(MPHashMap::with_hasher(hasher)),
+            schema: OnceCell::new(),
+        }
+    }
+    /// The parser of the db.
+    pub fn parser(&self) -> &P {
+        &self.mpparser
+    }
+    /// The productions in the grammar of the db.
+    pub fn schema(&self) -> &Schema {
+        self.schema.get_or_init(|| {
+            Schema::from_grammar_with_var_names(self.mpparser.grammar(), &self.mpparser.var_names())
+                .expect("grammar already used by the parser")
+        })
+    }
+    /// Adds the sentence for the value to the db.
+    pub fn tell_typed<T: Sentence>(&'a self, value: &T) -> Told {
+        let text = value.to_sentence();
+        self.tell_fact(unsafe { unbound(&text) })
+    }
+    /// Asks the db with the pattern and, for each answer, returns the value for
+    /// the first sentence in the pattern that, with its variables replaced by the
+    /// text bound to them in the answer, has the form of the type.
+    pub fn ask_typed<T: Sentence>(&'a self, pattern: &str) -> Vec<T> {
+        let pattern = unsafe { unbound(pattern) };
+        let ParseResult { facts } = self.mpparser.parse_text(pattern).expect("parse result");
+        self.ask(pattern)
+            .iter()
+            .filter_map(|matching| {
+                facts.iter().find_map(|fact| {
+                    let (text, _) = self.fill(fact, |var| binding(matching, var));
+                    self.parse_typed(&text)
+                })
+            })
+            .collect()
+    }
+    /// The value for a sentence, without terminator, if it can be parsed
+    /// and has the form of the type.
+    pub fn parse_typed<T: Sentence>(&'a self, sentence: &str) -> Option<T> {
+        let parse_tree = self.mpparser.parse_tree(unsafe { unbound(sentence) }).ok()?;
+        T::from_tree(&Tree::from_pair(parse_tree))
+    }
+    /// Like `ask`, with the answers as the values bound to the variables in the query.
+    pub fn ask_bindings(&'a self, knowledge: &str) -> Vec<Bindings> {
+        let (facts, query) = self.parse_query(unsafe { unbound(knowledge) });
+        let mut vars = vec![];
+        for path in query.iter().flatten() {
+            if path.value.is_var && !vars.contains(&path.value) {
+                vars.push(path.value);
+            }
+        }
+        let lexicon = self.mpparser.lexicon();
+        self.facts
+            .ask_facts(query)
+            .iter()
+            .map(|matching| {
+                let mut bindings = Bindings::new(lexicon, &vars, matching);
+                if matching.values().any(|value| !value.is_leaf) {
+                    self.add_trees(&facts, matching, &mut bindings);
+                }
+                bindings
+            })
+            .collect()
+    }
+    /// The canonical text of the sentences in the db, in the order of their ids.
+    pub fn iter_facts(&'a self) -> impl Iterator<Item = String> + 'a {
+        let mut facts: Vec<(FactId, &'a FSNode<'a>)> = self
+            .sentences
+            .borrow()
+            .iter()
+            .map(|(id, node)| (*id, *node))
+            .collect();
+        facts.sort_by_key(|(id, _)| *id);
+        facts.into_iter().map(move |(_, node)| self.fact_text(node))
+    }
+    /// How many sentences there are in the db.
+    pub fn len(&self) -> usize {
+        self.sentences.borrow().len()
+    }
+    /// Whether there are no sentences in the db.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// How many sentences there are in the db, in total and by production.
+    pub fn stats(&'a self) -> Stats {
+        let lexicon = self.mpparser.lexicon();
+        let productions = self
+            .productions
+            .borrow()
+            .iter()
+            .filter(|(_, count)| **count > 0)
+            .filter_map(|(id, count)| Some((lexicon.rule_name(*id)?.to_string(), *count)))
+            .collect();
+        Stats {
+            sentences: self.len(),
+            productions,
+        }
+    }
+    /// The sentences in the knowledge that cannot be parsed, as `import` would
+    /// report them, or the whole knowledge if it cannot be split into sentences.
+    pub fn check(&'a self, knowledge: &str) -> Vec<Skipped> {
+        let knowledge = unsafe { unbound(knowledge) };
+        let skipped = |start: usize, sentence: &str, error: String| {
+            let LineColumn { line, column } = LineColumn::start().after(&knowledge[..start]);
+            Skipped {
+                line,
+                column,
+                offset: start as u64,
+                sentence: sentence.trim().to_string(),
+                error,
+            }
+        };
+        match self.mpparser.parse_text(knowledge) {
+            Ok(ParseResult { facts }) => facts
+                .iter()
+                .filter_map(|fact| {
+                    let error = self.mpparser.parse_tree(fact).err()?;
+                    let start = fact.as_ptr() as usize - knowledge.as_ptr() as usize;
+                    Some(skipped(start, fact, error.to_string()))
+                })
+                .collect(),
+            Err(error) => {
+                let start = knowledge.len() - knowledge.trim_start().len();
+                vec![skipped(start, knowledge, error.to_string())]
+            }
+        }
+    }
+    /// The parse trees of the sentences in the knowledge.
+    pub fn trees(&'a self, knowledge: &str) -> Vec<Tree> {
+        let knowledge = unsafe { unbound(knowledge) };
+        let ParseResult { facts } = self.mpparser.parse_text(knowledge).expect("parse result");
+        facts
+            .iter()
+            .map(|fact| Tree::from_pair(self.mpparser.parse_tree(fact).expect("fact pair")))
+            .collect()
+    }
+    /// Writes the sentences in the db as knowledge that can be told to another db,
+    /// each followed by the terminator of its parser.
+    pub fn dump<W: io::Write>(&'a self, mut writer: W) -> io::Result<()> {
+        let terminator = self.mpparser.terminator();
+        for fact in self.iter_facts() {
+            if terminator.ends_with('\n') {
+                write!(writer, "{}{}", fact, terminator)?;
+            } else {
+                writeln!(writer, "{} {}", fact, terminator)?;
+            }
+        }
+        writer.flush()
+    }
+    /// Tells the knowledge read from the reader, a few sentences at a time, so that
+    /// it is never all in memory at once, logging the progress made. Returns how much
+    /// was told or, if some of the input could not be read or parsed, where.
+    pub fn tell_reader<R: io::BufRead>(&'a self, reader: R) -> Result<Progress, TellError> {
+        self.tell_reader_with_progress(reader, log_progress)
+    }
+    /// Tells the knowledge in the file at the path, like `tell_reader`.
+    pub fn tell_file<Q: AsRef<Path>>(&'a self, path: Q) -> Result<Progress, TellError> {
+        self.tell_reader(open(path)?)
+    }
+    /// Like `tell_reader`, calling `progress` each time some sentences have been told.
+    pub fn tell_reader_with_progress<R, F>(
+        &'a self,
+        reader: R,
+        progress: F,
+    ) -> Result<Progress, TellError>
+    where
+        R: io::BufRead,
+        F: FnMut(&Progress),
+    {
+        self.tell_stream(reader, progress, None)
+    }
+    /// Tells the knowledge, skipping the sentences that cannot be parsed,
+    /// and reports what was done with each sentence.
+    pub fn import(&'a self, knowledge: &str) -> ImportReport {
+        self.import_reader(knowledge.as_bytes())
+            .expect("knowledge in memory is valid UTF-8")
+    }
+    /// Like `tell_reader`, skipping the sentences that cannot be parsed.
+    /// Only fails if the input cannot be read, or is not valid UTF-8.
+    pub fn import_reader<R: io::BufRead>(&'a self, reader: R) -> Result<ImportReport, TellError> {
+        let mut report = ImportReport::default();
+        self.tell_stream(reader, log_progress, Some(&mut report))?;
+        Ok(report)
+    }
+    /// Imports the knowledge in the file at the path, like `import_reader`.
+    pub fn import_file<Q: AsRef<Path>>(&'a self, path: Q) -> Result<ImportReport, TellError> {
+        self.import_reader(open(path)?)
+    }
+    /// Tells the knowledge read from the reader. With a report, the sentences
+    /// that cannot be parsed are recorded and skipped; otherwise, the first
+    /// of them is returned as an error.
+    ///
+    /// The knowledge read so far, up to its last terminator, is split into sentences,
+    /// and all but the last are told; the last is kept until more is read, since it
+    /// may be part of a comment that spans several terminators, and so is, once,
+    /// the first sentence that cannot be parsed.
+    fn tell_stream<R, F>(
+        &'a self,
+        mut reader: R,
+        mut progress: F,
+        mut report: Option<&mut ImportReport>,
+    ) -> Result<Progress, TellError>
+    where
+        R: io::BufRead,
+        F: FnMut(&Progress),
+    {
+        let mut told = Progress::default();
+        let mut position = LineColumn::start();
+        let mut pending: Vec<u8> = vec![];
+        let mut wanted = READ_CHUNK_SIZE;
+        let mut eof = false;
+        let mut retried = None;
+        loop {
+            while !eof && pending.len() < wanted {
+                let offset = told.bytes + pending.len() as u64;
+                match reader.fill_buf() {
+                    Ok([]) => eof = true,
+                    Ok(buf) => {
+                        let len = buf.len();
+                        pending.extend_from_slice(buf);
+                        reader.consume(len);
+                    }
+                    Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+                    Err(error) => return Err(TellError::Io { offset, error }),
+                }
+            }
+            // a character split between reads is completed by the next one
+            let (valid, invalid) = match str::from_utf8(&pending) {
+                Ok(text) => (text.len(), false),
+                Err(error) => (error.valid_up_to(), eof || error.error_len().is_some()),
+            };
+            let end = eof && !invalid;
+            // the text is copied into the lexicon before `pending` changes
+            let text = unsafe { unbound(str::from_utf8_unchecked(&pending[..valid])) };
+            if end && text.trim().is_empty() {
+                progress(&told);
+                return Ok(told);
+            }
+            let terminated = self
+                .mpparser
+                .terminators()
+                .iter()
+                .filter_map(|t| text.rfind(t).map(|start| start + t.len()))
+                .max()
+                .unwrap_or(0);
+            let mut parsed = if end { text } else { &text[..terminated] };
+            let mut unterminated = false;
+            let facts = match self.mpparser.parse_text(parsed) {
+                Ok(ParseResult { facts }) => facts,
+                Err(_) if end && report.is_some() => {
+                    // the sentences up to the last terminator may still be fine
+                    unterminated = true;
+                    parsed = &text[..terminated];
+                    match self.mpparser.parse_text(parsed) {
+                        Ok(ParseResult { facts }) => facts,
+                        Err(_) => {
+                            parsed = "";
+                            vec![]
+                        }
+                    }
+                }
+                Err(error) if end => {
+                    return Err(TellError::Knowledge {
+                        offset: told.bytes,
+                        message: error.to_string(),
+                    })
+                }
+                Err(_) => vec![],
+            };
+            let start_of = |fact: &str| fact.as_ptr() as usize - text.as_ptr() as usize;
+            let complete = if end || invalid {
+                facts.len()
+            } else {
+                facts.len().saturating_sub(1)
+            };
+            let offset = told.bytes;
+            let mut done = 0;
+            let mut sentences = 0;
+            for fact in &facts[..complete] {
+                let start = start_of(fact);
+                if let Err(error) = self.mpparser.parse_tree(fact) {
+                    let at = offset + start as u64;
+                    if !end && !invalid && retried != Some(at) {
+                        retried = Some(at);
+                        break;
+                    }
+                    let message = error.to_string();
+                    match report {
+                        Some(ref mut report) => {
+                            let LineColumn { line, column } = position.after(&text[..start]);
+                            report.skipped.push(Skipped {
+                                line,
+                                column,
+                                offset: at,
+                                sentence: fact.trim().to_string(),
+                                error: message,
+                            });
+                        }
+                        None => {
+                            return Err(TellError::Sentence {
+                                offset: at,
+                                message,
+                            })
+                        }
+                    }
+                } else {
+                    let Told { status, .. } = self.tell_fact(fact);
+                    if let Some(ref mut report) = report {
+                        report.record(status);
+                    }
+                    sentences += 1;
+                }
+                done += 1;
+            }
+            let mut told_up_to = match facts.get(done) {
+                Some(fact) => start_of(fact),
+                None if end || invalid => parsed.len(),
+                None => 0,
+            };
+            if let (true, Some(ref mut report)) = (unterminated, &mut report) {
+                let rest = &text[told_up_to..];
+                let start = told_up_to + rest.len() - rest.trim_start().len();
+                let sentence = rest.trim();
+                let error = match self.mpparser.parse_tree(sentence) {
+                    Err(error) => error.to_string(),
+                    Ok(_) => {
+                        let end = Position::new(sentence, sentence.len()).expect("end of sentence");
+                        let message = String::from("expected a terminator");
+                        let variant = ErrorVariant::<P::Rule>::CustomError { message };
+                        Error::new_from_pos(variant, end).to_string()
+                    }
+                };
+                let LineColumn { line, column } = position.after(&text[..start]);
+                report.skipped.push(Skipped {
+                    line,
+                    column,
+                    offset: offset + start as u64,
+                    sentence: sentence.to_string(),
+                    error,
+                });
+                told_up_to = text.len();
+            }
+            position = position.after(&text[..told_up_to]);
+            drop(facts);
+            pending.drain(..told_up_to);
+            told.bytes += told_up_to as u64;
+            told.sentences += sentences;
+            if sentences > 0 {
+                progress(&told);
+            }
+            if end {
+                return Ok(told);
+            } else if done == 0 && invalid {
+                return Err(TellError::Utf8 {
+                    offset: offset + valid as u64,
+                });
+            } else if done == 0 {
+                wanted = wanted.max(pending.len()) * 2;
+            } else {
+                wanted = READ_CHUNK_SIZE.max(pending.len() * 2);
+            }
+        }
+    }
+    /// The knowledge, with the variables in it replaced by the values bound to them.
+    pub fn instantiate(&'a self, knowledge: &str, bindings: &Bindings) -> String {
+        let knowledge = unsafe { unbound(knowledge) };
+        let ParseResult { facts } = self.mpparser.parse_text(knowledge).expect("parse result");
+        let mut text = String::new();
+        let mut offset = 0;
+        for fact in facts {
+            let start = fact.as_ptr() as usize - knowledge.as_ptr() as usize;
+            text.push_str(&knowledge[offset..start]);
+            text.push_str(&self.fill(fact, |var| bindings.get(var)).0);
+            offset = start + fact.len();
+        }
+        text.push_str(&knowledge[offset..]);
+        text
+    }
+    fn parse_query(&'a self, knowledge: &'a str) -> (Vec<&'a str>, Vec<Vec<MPPath<'a>>>) {
+        let ParseResult { facts, .. } = self.mpparser.parse_text(knowledge).expect("parse result");
+        let query = facts
+            .iter()
+            .map(|fact| self.mpparser.parse_fact(fact))
+            .collect();
+        (facts, query)
+    }
+    /// Adds a single sentence to the db, unless it is already there,
+    /// forgetting the sentences that it replaces.
+    fn tell_fact(&'a self, fact: &'a str) -> Told {
+        let (id, template, paths) = self.mpparser.parse_fact_with_template(fact);
+        if let Some(last) = self.facts.find_fact(&paths) {
+            // the sentence in the db may differ from the one told in literal
+            // text, which is not part of its paths, or its id may have collided
+            let id = FactId(last.id());
+            let status = TellStatus::Present;
+            return Told { id, status };
+        }
+        let Addition {
+            last,
+            replaced,
+            removed,
+        } = self.facts.add_fact(paths, template);
+        let mut sentences = self.sentences.borrow_mut();
+        for node in removed {
+            if sentences.remove(&FactId(node.id())).is_some() {
+                self.count(node.template(), false);
+            }
+        }
+        // a sentence whose hash collides with that of another sentence
+        // in the db takes the next free id
+        let mut id = id;
+        while sentences.contains_key(&id) {
+            id = FactId(id.0.wrapping_add(1));
+        }
+        last.set_id(id.0);
+        sentences.insert(id, last);
+        self.count(template, true);
+        let status = if replaced {
+            TellStatus::Replaced
+        } else {
+            TellStatus::Added
+        };
+        Told { id, status }
+    }
+    /// Counts a sentence with the template as added to the db, or removed from it.
+    fn count(&self, template: u64, added: bool) {
+        let lexicon = self.mpparser.lexicon();
+        let production = lexicon.template_production(template).unwrap_or(0);
+        let mut productions = self.productions.borrow_mut();
+        let count = productions.entry(production).or_insert(0);
+        if added {
+            *count += 1;
+        } else {
+            *count = count.saturating_sub(1);
+        }
+    }
+    /// The canonical text of the sentence that ends at the node.
+    fn fact_text(&self, last: &FSNode<'a>) -> String {
+        let leaves = last.leaves();
+        let values: Vec<&str> = leaves.iter().map(|value| value.text.as_str()).collect();
+        self.mpparser
+            .lexicon()
+            .fill_template(last.template(), &values)
+            .expect("template of a sentence")
+    }
+    /// The text of a sentence, with its variables replaced by their values,
+    /// along with the variables that have been replaced and where their values start.
+    fn fill<'v, F>(&'a self, fact: &'a str, value_of: F) -> (String, Vec<(&'a str, usize)>)
+    where
+        F: Fn(&str) -> Option<&'v str>,
+    {
+        let var_names = self.mpparser.var_names();
+        let parse_tree = self.mpparser.parse_tree(fact).expect("fact pair");
+        let mut text = String::new();
+        let mut filled = vec![];
+        let mut offset = 0;
+        for pair in parse_tree.into_inner().flatten() {
+            if !var_names.is_var(&pair.as_rule().rule_name()) {
+                continue;
+            }
+            if let Some(value) = value_of(pair.as_str()) {
+                let span = pair.as_span();
+                text.push_str(&fact[offset..span.start()]);
+                filled.push((pair.as_str(), text.len()));
+                text.push_str(value);
+                offset = span.end();
+            }
+        }
+        text.push_str(&fact[offset..]);
+        (text, filled)
+    }
+    /// Adds to the bindings the structure of the non terminal values,
+    /// as found in the query once its variables are replaced.
+    fn add_trees(&'a self, facts: &[&'a str], matching: &MPMatching<'a>, bindings: &mut Bindings) {
+        for fact in facts {
+            let (text, filled) = self.fill(fact, |var| binding(matching, var));
+            let pending: Vec<(&str, usize)> = filled
+                .into_iter()
+                .filter(|(var, _)| {
+                    let is_leaf = matching
+                        .iter()
+                        .any(|(key, value)| key.text == *var && value.is_leaf);
+                    let binding = bindings.binding(var);
+                    !is_leaf && binding.is_some_and(|binding| binding.tree.is_none())
+                })
+                .collect();
+            if pending.is_empty() {
+                continue;
+            }
+            let parse_tree = self
+                .mpparser
+                .parse_tree(unsafe { unbound(&text) })
+                .expect("fact pair");
+            let pairs: Vec<_> = std::iter::once(parse_tree.clone())
+                .chain(parse_tree.into_inner().flatten())
+                .collect();
+            for (var, start) in pending {
+                let production = bindings.production(var).unwrap_or_default().to_string();
+                let pair = pairs.iter().find(|pair| {
+                    pair.as_span().start() == start && pair.as_rule().rule_name() == production
+                });
+                if let Some(pair) = pair {
+                    bindings.set_tree(var, Tree::from_pair(pair.clone()));
+                }
+            }
+        }
+    }
+}
+
+/// Gives the text the lifetime of the db. The parser needs that, but
+/// the db does not keep any reference to the text it is told or asked,
+/// since the text of each segment is copied into the lexicon,
+/// so the text only needs to live while it is parsed.
+unsafe fn unbound<'a>(text: &str) -> &'a str {
+    mem::transmute(text)
+}
+
+fn open<Q: AsRef<Path>>(path: Q) -> Result<io::BufReader<File>, TellError> {
+    let file = File::open(path).map_err(|error| TellError::Io { offset: 0, error })?;
+    Ok(io::BufReader::new(file))
+}
+
+fn log_progress(progress: &Progress) {
+    info!(
+        "Told {} sentences, {} bytes",
+        progress.sentences, progress.bytes
+    );
+}
+
+impl<'a, P: SentenceParser<'a>> DataBase<'a> for MPDB<'a, P> {
+    fn tell(&'a self, knowledge: &str) -> Vec<Told> {
+        let knowledge = unsafe { unbound(knowledge) };
+        let result = self.mpparser.parse_text(knowledge.trim());
+        if result.is_err() {
+            panic!("Parsing problem! {}", result.err().unwrap());
+        } else {
+            let ParseResult { facts } = result.ok().unwrap();
+            facts.iter().map(|fact| self.tell_fact(fact)).collect()
+        }
+    }
+    fn ask(&'a self, knowledge: &str) -> Vec<MPMatching<'a>> {
+        let (_, query) = self.parse_query(unsafe { unbound(knowledge) });
+        self.facts.ask_facts(query)
+    }
+    fn get(&'a self, id: FactId) -> Option<String> {
+        let last = *self.sentences.borrow().get(&id)?;
+        Some(self.fact_text(last))
+    }
+    fn forget(&'a self, id: FactId) -> bool {
+        let last = match self.sentences.borrow().get(&id) {
+            Some(last) => *last,
+            None => return false,
+        };
+        let template = last.template();
+        let text = self.fact_text(last);
+        let paths = self.mpparser.parse_fact(unsafe { unbound(&text) });
+        if !self.facts.remove_fact(paths) {
+            return false;
+        }
+        self.sentences.borrow_mut().remove(&id);
+        self.count(template, false);
+        true
+    }
+}
@@ -0,0 +1,182 @@
+// Copyright (c) 2020 by Enrique Pérez Arnaud <enrique at cazalla.net>
+//
+// This file is part of the modus_ponens project.
+// http://www.modus_ponens.net
+//
+// The modus_ponens project is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// The modus_ponens project is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with any part of the modus_ponens project.
+// If not, see <http://www.gnu.org/licenses/>.
+
+//! Dbs for grammars that are only known at runtime.
+//!
+//! The grammar is compiled with pest's meta parser and run on pest's VM,
+//! and the resulting parse trees are turned into paths exactly as for the dbs
+//! obtained with `#[derive(DBGen)]`, with the same conventions for `fact`,
+//! `var`, `v_` and `v_u_`.
+//! The syntax of knowledge is the default one unless given as a `KnowledgeSyntax`,
+//! the runtime counterpart of the `comment`, `whitespace` and `string` attributes.
+
+use pest::error::{Error, ErrorVariant};
+use pest::iterators::Pair;
+use pest::Position;
+use pest_meta::optimizer;
+use pest_meta::parser::Rule as GrammarRule;
+use pest_vm::Vm;
+
+use crate::db::MPDB;
+use crate::hashing::MPBuildHasher;
+use crate::kparser;
+use crate::lexicon::Lexicon;
+use crate::parse_result::ParseResult;
+use crate::schema::{parse_grammar, Schema};
+use crate::sentence::{SentenceParser, VarNames};
+
+/// The syntax of knowledge, as pest expressions for comments, whitespace
+/// and strings, where `None` stands for the default comments and whitespace,
+/// and for no strings. Sentences end with the default terminators.
+#[derive(Debug, Clone, Default)]
+pub struct KnowledgeSyntax {
+    pub comment: Option<String>,
+    pub whitespace: Option<String>,
+    pub string: Option<String>,
+}
+
+impl KnowledgeSyntax {
+    /// The default syntax, with double quoted strings, within which
+    /// terminators do not end sentences.
+    pub fn with_strings() -> KnowledgeSyntax {
+        KnowledgeSyntax {
+            string: Some(kparser::DEFAULT_STRING.to_string()),
+            ..KnowledgeSyntax::default()
+        }
+    }
+}
+
+pub struct DynamicParser {
+    lexicon: Box<Lexicon>,
+    grammar: String,
+    var_names: VarNames,
+    vm: Vm,
+    knowledge: Option<Vm>,
+}
+
+impl DynamicParser {
+    /// A parser for the grammar, which must follow the conventions of uindex,
+    /// as checked by `Schema::check`; otherwise the errors are returned,
+    /// as errors at the start of the grammar.
+    pub fn from_grammar(grammar: &str) -> Result<DynamicParser, Vec<Error<GrammarRule>>> {
+        DynamicParser::from_grammar_with_hasher(grammar, MPBuildHasher::default())
+    }
+    pub fn from_grammar_with_hasher(
+        grammar: &str,
+        hasher: MPBuildHasher,
+    ) -> Result<DynamicParser, Vec<Error<GrammarRule>>> {
+        DynamicParser::from_grammar_with_var_names(grammar, VarNames::default(), hasher)
+    }
+    /// A parser for a grammar in which variables and the productions that they
+    /// can match are named as given.
+    pub fn from_grammar_with_var_names(
+        grammar: &str,
+        var_names: VarNames,
+        hasher: MPBuildHasher,
+    ) -> Result<DynamicParser, Vec<Error<GrammarRule>>> {
+        let ast = parse_grammar(grammar)?;
+        let schema = Schema::from_grammar_with_var_names(grammar, &var_names)?;
+        schema.check().map_err(|errors| {
+            errors
+                .iter()
+                .map(|error| {
+                    let message = error.to_string();
+                    let start = Position::from_start(grammar);
+                    Error::new_from_pos(ErrorVariant::CustomError { message }, start)
+                })
+                .collect::<Vec<_>>()
+        })?;
+        Ok(DynamicParser {
+            lexicon: Box::new(Lexicon::with_hasher(hasher)),
+            grammar: grammar.to_string(),
+            var_names,
+            vm: Vm::new(optimizer::optimize(ast)),
+            knowledge: None,
+        })
+    }
+    /// The parser, splitting knowledge into sentences with the given syntax.
+    /// The errors are those in the pest expressions of the syntax, as errors
+    /// in the grammar for knowledge obtained from `kparser::knowledge_grammar`.
+    pub fn with_syntax(
+        mut self,
+        syntax: &KnowledgeSyntax,
+    ) -> Result<DynamicParser, Vec<Error<GrammarRule>>> {
+        let grammar = kparser::knowledge_grammar(
+            &[],
+            syntax.comment.as_deref(),
+            syntax.whitespace.as_deref(),
+            syntax.string.as_deref(),
+        );
+        let ast = parse_grammar(&grammar)?;
+        self.knowledge = Some(Vm::new(optimizer::optimize(ast)));
+        Ok(self)
+    }
+}
+
+impl<'a> SentenceParser<'a> for DynamicParser {
+    type Rule = &'a str;
+
+    fn lexicon(&self) -> &Lexicon {
+        &self.lexicon
+    }
+
+    fn grammar(&self) -> &str {
+        &self.grammar
+    }
+
+    fn var_names(&self) -> VarNames {
+        self.var_names
+    }
+
+    fn parse_tree(&'a self, text: &'a str) -> Result<Pair<'a, &'a str>, Error<&'a str>> {
+        Ok(self.vm.parse("fact", text)?.next().expect("fact pair"))
+    }
+
+    fn parse_text(&'a self, text: &'a str) -> Result<ParseResult<'a>, Error<kparser::Rule>> {
+        match &self.knowledge {
+            Some(vm) => kparser::read_knowledge(vm.parse("knowledge", text), text),
+            None => kparser::parse_knowledge::<kparser::KParser, _>(kparser::Rule::knowledge, text),
+        }
+    }
+}
+
+pub type DynamicDB<'a> = MPDB<'a, DynamicParser>;
+
+impl<'a> MPDB<'a, DynamicParser> {
+    pub fn from_grammar(grammar: &str) -> Result<DynamicDB<'a>, Vec<Error<GrammarRule>>> {
+        Ok(MPDB::new(DynamicParser::from_grammar(grammar)?))
+    }
+    pub fn from_grammar_with_hasher(
+        grammar: &str,
+        hasher: MPBuildHasher,
+    ) -> Result<DynamicDB<'a>, Vec<Error<GrammarRule>>> {
+        Ok(MPDB::new(DynamicParser::from_grammar_with_hasher(
+            grammar, hasher,
+        )?))
+    }
+    /// A db for the grammar, for knowledge with the given syntax.
+    pub fn from_grammar_with_syntax(
+        grammar: &str,
+        syntax: &KnowledgeSyntax,
+    ) -> Result<DynamicDB<'a>, Vec<Error<GrammarRule>>> {
+        Ok(MPDB::new(
+            DynamicParser::from_grammar(grammar)?.with_syntax(syntax)?,
+        ))
+    }
+}
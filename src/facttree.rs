@@ -17,17 +17,17 @@
 // along with any part of the modus_ponens project.
 // If not, see <http://www.gnu.org/licenses/>.
 
-use std::cell::RefCell;
+use std::cell::{Cell, OnceCell, RefCell};
 use std::clone::Clone;
-use std::collections::HashMap;
-use std::lazy::OnceCell;
 use std::mem;
+use std::ptr;
 
+use crate::hashing::{MPBuildHasher, MPHashMap};
 use crate::matching::MPMatching;
-use crate::path::MPPath;
+use crate::path::{MPPath, PathKey};
 use crate::segment::MPSegment;
 
-pub struct CarryOver<'a>(HashMap<usize, &'a FSNode<'a>>);
+pub struct CarryOver<'a>(MPHashMap<usize, &'a FSNode<'a>>);
 
 impl<'a> CarryOver<'a> {
     pub fn add(mut self, index: usize, node: &'a FSNode<'a>) -> Self {
@@ -40,27 +40,79 @@ impl<'a> CarryOver<'a> {
     }
 }
 
-fn mk_children<'a>() -> RefCell<HashMap<u64, &'a FSNode<'a>>> {
-    RefCell::new(HashMap::with_capacity(0))
+fn mk_children<'a>(hasher: MPBuildHasher) -> RefCell<MPHashMap<PathKey, &'a FSNode<'a>>> {
+    RefCell::new(MPHashMap::with_capacity_and_hasher(0, hasher))
 }
 
 pub struct FactSet<'a> {
     pub root: Box<FSNode<'a>>,
+    hasher: MPBuildHasher,
+}
+
+impl<'a> Default for FactSet<'a> {
+    fn default() -> Self {
+        FactSet::new()
+    }
 }
 
 impl<'a> FactSet<'a> {
     pub fn new() -> FactSet<'a> {
+        FactSet::with_hasher(MPBuildHasher::default())
+    }
+    pub fn with_hasher(hasher: MPBuildHasher) -> FactSet<'a> {
         FactSet {
             root: Box::new(FSNode::new(None)),
+            hasher,
+        }
+    }
+    /// Adds the fact to the set, marking its last node with the template
+    /// it was parsed with.
+    pub fn add_fact(&'a self, fact: Vec<MPPath<'a>>, template: u64) -> Addition<'a> {
+        let carry = CarryOver(MPHashMap::with_hasher(self.hasher));
+        let mut addition = Addition {
+            last: &self.root,
+            replaced: false,
+            removed: vec![],
+        };
+        self.follow_and_create_paths(&self.root, fact, carry, &mut addition);
+        if !ptr::eq(addition.last, &*self.root) {
+            addition.last.template.set(template);
+        }
+        let mut removed = mem::take(&mut addition.removed);
+        removed.retain(|node| !self.holds(node));
+        addition.removed = removed;
+        addition
+    }
+    /// The last node of the fact, if it is in the set. The node may have
+    /// children, if the fact is a prefix of some other fact.
+    pub fn find_fact(&'a self, fact: &[MPPath<'a>]) -> Option<&'a FSNode<'a>> {
+        let last = self.follow_paths(fact)?.last()?.child;
+        if last.template() != 0 {
+            Some(last)
+        } else {
+            None
         }
     }
-    pub fn add_fact(&'a self, fact: Vec<MPPath<'a>>) {
-        let carry = CarryOver(HashMap::new());
-        self.follow_and_create_paths(&self.root, fact, carry);
+    /// Whether the node can still be reached from the root of the set,
+    /// following the parents it was added under.
+    pub fn holds(&self, mut node: &'a FSNode<'a>) -> bool {
+        while let Some((parent, key)) = node.parent {
+            let logic = node.value.is_some_and(|value| value.in_var_range);
+            let held = if logic {
+                parent.get_lchild(key)
+            } else {
+                parent.get_child(key)
+            };
+            if !held.is_some_and(|held| ptr::eq(held, node)) {
+                return false;
+            }
+            node = parent;
+        }
+        ptr::eq(node, &*self.root)
     }
     pub fn ask_fact(&'a self, fact: Vec<MPPath<'a>>) -> (Vec<MPMatching<'a>>, Vec<MPPath<'a>>) {
         let response: Vec<MPMatching> = vec![];
-        let matching: MPMatching = HashMap::new();
+        let matching: MPMatching = MPHashMap::with_hasher(self.hasher);
         let paths: &[MPPath] = unsafe { mem::transmute(fact.as_slice()) };
         let npaths = vec![paths];
         let qpaths: &[&[MPPath]] = unsafe { mem::transmute(npaths.as_slice()) };
@@ -71,11 +123,11 @@ impl<'a> FactSet<'a> {
     }
     pub fn ask_fact_bool(&'a self, fact: Vec<MPPath<'a>>) -> (bool, Vec<MPPath<'a>>) {
         let (resp, fact) = self.ask_fact(fact);
-        (resp.len() > 0, fact)
+        (!resp.is_empty(), fact)
     }
     pub fn ask_facts(&'a self, facts: Vec<Vec<MPPath<'a>>>) -> Vec<MPMatching<'a>> {
         let response: Vec<MPMatching> = vec![];
-        let matching: MPMatching = HashMap::new();
+        let matching: MPMatching = MPHashMap::with_hasher(self.hasher);
         let paths: Vec<&[MPPath]> = facts.iter().map(|fact| fact.as_slice()).collect();
         let qpaths: &[&[MPPath]] = unsafe { mem::transmute(paths.as_slice()) };
         let response = self
@@ -83,15 +135,98 @@ impl<'a> FactSet<'a> {
             .query_paths(qpaths, matching, response, Some(&(*self.root)));
         response
     }
+    /// Removes the fact from the set, pruning the nodes that only led to it.
+    /// Returns whether the fact was in the set.
+    pub fn remove_fact(&'a self, fact: Vec<MPPath<'a>>) -> bool {
+        let edges = match self.follow_paths(&fact) {
+            Some(edges) => edges,
+            None => return false,
+        };
+        let last = match edges.last() {
+            Some(edge) if edge.child.template() != 0 => edge.child,
+            _ => return false,
+        };
+        last.template.set(0);
+        for (position, edge) in edges.iter().enumerate().rev() {
+            // nodes where other facts end, or that lead to them, are kept
+            if !edge.child.is_terminal() || edge.child.template() != 0 {
+                continue;
+            }
+            // and so are non terminal values that other facts end with,
+            // which is where the last leaf within the value ends a fact
+            if edge.child.value.is_some_and(|value| !value.is_leaf) {
+                let within = fact[edge.index].paths_within(&fact[edge.index + 1..]);
+                let end = edges[position..]
+                    .iter()
+                    .filter(|other| other.index <= edge.index + within)
+                    .rfind(|other| other.child.value.is_some_and(|value| value.is_leaf));
+                if end.is_some_and(|end| end.child.template() != 0) {
+                    continue;
+                }
+            }
+            edge.parent.remove_child(edge.key, edge.logic);
+        }
+        true
+    }
+    /// The edges along which the fact is stored, in the order in which
+    /// they are followed, or `None` if the fact is not in the set.
+    fn follow_paths(&'a self, paths: &[MPPath<'a>]) -> Option<Vec<Edge<'a>>> {
+        let mut edges = vec![];
+        let mut carry: MPHashMap<usize, &'a FSNode<'a>> = MPHashMap::with_hasher(self.hasher);
+        let mut parent: &'a FSNode<'a> = &self.root;
+        for (index, path) in paths.iter().enumerate() {
+            if path.value.is_empty {
+                continue;
+            }
+            let logic = path.value.in_var_range;
+            let child = if logic {
+                parent.get_lchild(path.identity)?
+            } else {
+                parent.get_child(path.identity)?
+            };
+            let key = path.identity;
+            edges.push(Edge {
+                index,
+                parent,
+                key,
+                child,
+                logic,
+            });
+            if let Some(more) = carry.remove(&index) {
+                let held = if logic {
+                    more.get_lchild(key)
+                } else {
+                    more.get_child(key)
+                };
+                if held.is_some_and(|held| ptr::eq(held, child)) {
+                    edges.push(Edge {
+                        index,
+                        parent: more,
+                        key,
+                        child,
+                        logic,
+                    });
+                }
+            }
+            if logic && !path.value.is_leaf {
+                // keyed as in `follow_and_create_paths`
+                carry.insert(index + 1 + path.paths_within(&paths[index + 1..]), child);
+                continue;
+            }
+            parent = child;
+        }
+        Some(edges)
+    }
     pub fn follow_and_create_paths(
         &'a self,
         mut parent: &'a FSNode<'a>,
         mut paths: Vec<MPPath<'a>>,
         mut carry: CarryOver<'a>,
+        addition: &mut Addition<'a>,
     ) {
         let mut child: &FSNode;
         let mut path_index = 0;
-        while paths.len() > 0 {
+        while !paths.is_empty() {
             let path = paths.remove(0);
             if path.value.is_empty {
                 path_index += 1;
@@ -99,9 +234,9 @@ impl<'a> FactSet<'a> {
             }
             if path.value.in_var_range {
                 let opt_child = parent.get_lchild(path.identity);
-                let reindex = path.paths_after(&paths);
-                if opt_child.is_some() {
-                    child = opt_child.expect("node");
+                let reindex = path_index + 1 + path.paths_within(&paths);
+                if let Some(old_child) = opt_child {
+                    child = old_child;
                     if !path.value.is_leaf {
                         carry = carry.add(reindex, child);
                         path_index += 1;
@@ -109,8 +244,7 @@ impl<'a> FactSet<'a> {
                     }
                 } else if path.value.is_leaf {
                     paths.insert(0, path);
-                    self.create_paths(parent, paths, carry, path_index);
-                    return;
+                    return self.create_paths(parent, paths, carry, path_index, addition);
                 } else {
                     let unique_child = path.value.unique;
                     let path_id = path.identity;
@@ -122,6 +256,7 @@ impl<'a> FactSet<'a> {
                         child_node,
                         carry,
                         path_index,
+                        addition,
                     );
                     child = new_child;
                     carry = new_carry;
@@ -132,18 +267,18 @@ impl<'a> FactSet<'a> {
                 }
             } else {
                 let path_id = path.identity;
-                let opt_child = parent.get_child(path_id);
-                if opt_child.is_none() {
-                    paths.insert(0, path);
-                    self.create_paths(parent, paths, carry, path_index);
-                    return;
-                } else {
-                    child = opt_child.expect("node");
+                match parent.get_child(path_id) {
+                    Some(old_child) => child = old_child,
+                    None => {
+                        paths.insert(0, path);
+                        return self.create_paths(parent, paths, carry, path_index, addition);
+                    }
                 }
             }
             parent = child;
             path_index += 1;
         }
+        addition.last = parent;
     }
 
     fn create_paths(
@@ -152,10 +287,11 @@ impl<'a> FactSet<'a> {
         mut paths: Vec<MPPath<'a>>,
         mut carry: CarryOver<'a>,
         offset: usize,
+        addition: &mut Addition<'a>,
     ) {
         let mut child: &FSNode;
         let mut path_index = 0;
-        while paths.len() > 0 {
+        while !paths.is_empty() {
             let path = paths.remove(0);
             if path.value.is_empty {
                 path_index += 1;
@@ -165,9 +301,9 @@ impl<'a> FactSet<'a> {
             let unique_child = path.value.unique;
             let logic_node = path.value.in_var_range;
             let is_leaf = path.value.is_leaf;
-            let reindex = path.paths_after(&paths);
-            let child_node = FSNode::new(Some(path.value));
             let real_index = path_index + offset;
+            let reindex = real_index + 1 + path.paths_within(&paths);
+            let child_node = FSNode::new(Some(path.value));
             if logic_node {
                 let (new_child, new_carry) = self.intern_lchild(
                     parent,
@@ -176,6 +312,7 @@ impl<'a> FactSet<'a> {
                     child_node,
                     carry,
                     real_index,
+                    addition,
                 );
                 child = new_child;
                 carry = new_carry;
@@ -193,60 +330,103 @@ impl<'a> FactSet<'a> {
             parent = child;
             path_index += 1;
         }
+        addition.last = parent;
     }
     pub fn intern_child(
         &'a self,
         parent: &'a FSNode<'a>,
-        path_id: u64,
-        child: FSNode<'a>,
+        path_id: PathKey,
+        mut child: FSNode<'a>,
         mut carry: CarryOver<'a>,
         index: usize,
     ) -> (&'a FSNode<'a>, CarryOver<'a>) {
+        child.parent = Some((parent, path_id));
         let child_ref = Box::leak(Box::new(child));
         let (new_carry, more) = carry.node(index);
         carry = new_carry;
-        if more.is_some() {
-            let mut other_parent = more.unwrap().children.get_or_init(mk_children).borrow_mut();
+        if let Some(more) = more {
+            let mut other_parent = more
+                .children
+                .get_or_init(|| mk_children(self.hasher))
+                .borrow_mut();
             other_parent.insert(path_id, child_ref);
         }
-        let mut one_parent = parent.children.get_or_init(mk_children).borrow_mut();
+        let mut one_parent = parent
+            .children
+            .get_or_init(|| mk_children(self.hasher))
+            .borrow_mut();
         one_parent.insert(path_id, child_ref);
         (child_ref, carry)
     }
+    #[allow(clippy::too_many_arguments)]
     pub fn intern_lchild(
         &'a self,
         parent: &'a FSNode<'a>,
-        path_id: u64,
+        path_id: PathKey,
         unique_child: bool,
-        child: FSNode<'a>,
+        mut child: FSNode<'a>,
         mut carry: CarryOver<'a>,
         index: usize,
+        addition: &mut Addition<'a>,
     ) -> (&'a FSNode<'a>, CarryOver<'a>) {
+        child.parent = Some((parent, path_id));
         let child_ref = Box::leak(Box::new(child));
         let (new_carry, more) = carry.node(index);
         carry = new_carry;
-        if more.is_some() {
+        if let Some(more) = more {
             let mut other_parent = more
-                .unwrap()
                 .lchildren
-                .get_or_init(mk_children)
+                .get_or_init(|| mk_children(self.hasher))
                 .borrow_mut();
             other_parent.insert(path_id, child_ref);
         }
-        let mut one_parent = parent.lchildren.get_or_init(mk_children).borrow_mut();
-        if unique_child {
-            one_parent.clear();
+        let mut one_parent = parent
+            .lchildren
+            .get_or_init(|| mk_children(self.hasher))
+            .borrow_mut();
+        if unique_child && !one_parent.is_empty() {
+            addition.replaced = true;
+            for (_, node) in one_parent.drain() {
+                node.collect_ends(&mut addition.removed);
+            }
         }
         one_parent.insert(path_id, child_ref);
         (child_ref, carry)
     }
 }
 
+/// What adding a fact to a set did.
+pub struct Addition<'a> {
+    /// The last node of the fact.
+    pub last: &'a FSNode<'a>,
+    /// Whether, to add the fact, some value of a unique production had to be replaced.
+    pub replaced: bool,
+    /// The last nodes of the facts that are no longer in the set, having been replaced.
+    pub removed: Vec<&'a FSNode<'a>>,
+}
+
+/// A child in the set, as held by one of its parents.
+struct Edge<'a> {
+    /// The index in the fact of the path that leads to the child.
+    index: usize,
+    parent: &'a FSNode<'a>,
+    key: PathKey,
+    child: &'a FSNode<'a>,
+    logic: bool,
+}
+
 #[derive(Debug)]
 pub struct FSNode<'a> {
-    children: OnceCell<RefCell<HashMap<u64, &'a FSNode<'a>>>>,
-    lchildren: OnceCell<RefCell<HashMap<u64, &'a FSNode<'a>>>>,
+    children: OnceCell<RefCell<MPHashMap<PathKey, &'a FSNode<'a>>>>,
+    lchildren: OnceCell<RefCell<MPHashMap<PathKey, &'a FSNode<'a>>>>,
     value: Option<&'a MPSegment>,
+    /// The node that this one was added under, for the first fact that went
+    /// through it, along with its key there.
+    parent: Option<(&'a FSNode<'a>, PathKey)>,
+    /// For the last node of a fact, the template it was parsed with; otherwise 0.
+    template: Cell<u64>,
+    /// For the last node of a fact, the id that the db gave the fact.
+    id: Cell<u64>,
 }
 
 impl<'a> FSNode<'a> {
@@ -255,52 +435,85 @@ impl<'a> FSNode<'a> {
             children: OnceCell::new(),
             lchildren: OnceCell::new(),
             value,
+            parent: None,
+            template: Cell::new(0),
+            id: Cell::new(0),
         }
     }
-    pub fn get_child(&'a self, path_id: u64) -> Option<&'a Self> {
-        let children = self.children.get();
-        if children.is_none() {
-            return None;
-        }
-        let ch = children.unwrap().borrow();
-        match ch.get(&path_id) {
-            None => None,
-            Some(child_ref) => Some(*child_ref),
-        }
+    /// The template of the fact that ends at the node, or 0 if none does.
+    pub fn template(&self) -> u64 {
+        self.template.get()
     }
-    pub fn get_lchild(&'a self, path_id: u64) -> Option<&'a Self> {
-        let children = self.lchildren.get();
-        if children.is_none() {
-            return None;
-        }
-        let ch = children.unwrap().borrow();
-        match ch.get(&path_id) {
-            None => None,
-            Some(child_ref) => Some(*child_ref),
+    /// The id of the fact that ends at the node.
+    pub fn id(&self) -> u64 {
+        self.id.get()
+    }
+    pub fn set_id(&self, id: u64) {
+        self.id.set(id);
+    }
+    /// The values of the leaves of the fact that ends at the node, in order,
+    /// taken from the nodes along the way to it.
+    pub fn leaves(&self) -> Vec<&'a MPSegment> {
+        let mut leaves = vec![];
+        let mut node = self;
+        while let Some((parent, _)) = node.parent {
+            leaves.extend(node.value);
+            node = parent;
         }
+        leaves.reverse();
+        leaves
     }
-    pub fn get_child_r(&'a self, path: &'a MPPath<'a>) -> Option<&'a Self> {
-        let children = self.children.get();
-        if children.is_none() {
-            return None;
+    /// Pushes the nodes where facts end, at or below the node. The children
+    /// of non terminal values are not followed, since they are carried over
+    /// from the leaves within those values, and are found below them.
+    fn collect_ends(&'a self, ends: &mut Vec<&'a FSNode<'a>>) {
+        if self.value.is_some_and(|value| !value.is_leaf) {
+            return;
         }
-        let ch = children.unwrap().borrow();
-        match ch.get(&path.identity) {
-            None => None,
-            Some(child_ref) => Some(*child_ref),
+        if self.template() != 0 {
+            ends.push(self);
         }
-    }
-    pub fn get_lchild_r(&'a self, path: &'a MPPath<'a>) -> Option<&'a Self> {
-        let children = self.lchildren.get();
-        if children.is_none() {
-            return None;
+        for children in self.children.get().iter().chain(self.lchildren.get().iter()) {
+            for child in children.borrow().values() {
+                child.collect_ends(ends);
+            }
         }
-        let ch = children.unwrap().borrow();
-        match ch.get(&path.identity) {
-            None => None,
-            Some(child_ref) => Some(*child_ref),
+    }
+    /// Whether the node has no children. A node without children ends a fact,
+    /// but facts that are a prefix of others end at nodes with children,
+    /// marked with their template.
+    pub fn is_terminal(&self) -> bool {
+        let empty = |children: Option<&RefCell<MPHashMap<PathKey, &'a FSNode<'a>>>>| {
+            children.map_or(true, |children| children.borrow().is_empty())
+        };
+        empty(self.children.get()) && empty(self.lchildren.get())
+    }
+    fn remove_child(&self, path_id: PathKey, logic: bool) {
+        let children = if logic {
+            self.lchildren.get()
+        } else {
+            self.children.get()
+        };
+        if let Some(children) = children {
+            children.borrow_mut().remove(&path_id);
         }
     }
+    pub fn get_child(&'a self, path_id: PathKey) -> Option<&'a Self> {
+        let ch = self.children.get()?.borrow();
+        ch.get(&path_id).copied()
+    }
+    pub fn get_lchild(&'a self, path_id: PathKey) -> Option<&'a Self> {
+        let ch = self.lchildren.get()?.borrow();
+        ch.get(&path_id).copied()
+    }
+    pub fn get_child_r(&'a self, path: &MPPath) -> Option<&'a Self> {
+        let ch = self.children.get()?.borrow();
+        ch.get(&path.identity).copied()
+    }
+    pub fn get_lchild_r(&'a self, path: &MPPath) -> Option<&'a Self> {
+        let ch = self.lchildren.get()?.borrow();
+        ch.get(&path.identity).copied()
+    }
     pub fn query_paths(
         &'a self,
         all_all_paths: &'a [&'a [MPPath]],
@@ -308,44 +521,30 @@ impl<'a> FSNode<'a> {
         mut resp: Vec<MPMatching<'a>>,
         root: Option<&'a FSNode<'a>>,
     ) -> Vec<MPMatching<'a>> {
-        let rroot: &'a FSNode;
-        if root.is_some() {
-            rroot = root.unwrap();
-        } else {
-            rroot = self;
-        }
+        let rroot: &'a FSNode = root.unwrap_or(self);
         let (&new_all_paths, new_all_all) = all_all_paths.split_first().unwrap();
         let mut all_paths = new_all_paths;
 
-        let mut finished = false;
-        let mut next_path: Option<&MPPath> = None;
-        let mut next_paths: Option<&'a [MPPath]> = None;
-        while !finished {
-            let split_paths = all_paths.split_first();
-            if split_paths.is_some() {
-                let (path, paths) = split_paths.unwrap();
-                if !path.value.is_empty && path.value.is_leaf {
-                    finished = true;
-                    next_path = Some(path);
-                    next_paths = Some(paths);
-                } else {
-                    all_paths = paths;
-                }
-            } else {
-                finished = true;
+        let mut next_path: Option<(&MPPath, &'a [MPPath])> = None;
+        while let Some((path, paths)) = all_paths.split_first() {
+            if !path.value.is_empty && path.value.is_leaf {
+                next_path = Some((path, paths));
+                break;
             }
+            all_paths = paths;
         }
-        if next_path.is_some() {
-            let mut subs_path: Option<&MPPath> = None;
-            let path = next_path.unwrap();
-            let paths = next_paths.unwrap();
+        if let Some((path, paths)) = next_path {
+            let mut subs_path: Option<MPPath> = None;
             if path.value.is_var {
                 if !matching.contains_key(&path.value) {
-                    let lchildren = self.lchildren.get();
-                    if lchildren.is_some() {
+                    if let Some(lchildren) = self.lchildren.get() {
                         // If there is a variable in the question and this is its 1st ocurrence,
                         // recurse over all the logical children in the present node
-                        for lchild_node in lchildren.unwrap().borrow().values() {
+                        // that are in the same position in the sentence as the variable
+                        for (key, lchild_node) in lchildren.borrow().iter() {
+                            if key.chain != path.identity.chain {
+                                continue;
+                            }
                             let mut new_matching = matching.clone();
                             new_matching.insert(path.value, lchild_node.value.unwrap());
                             let mut npaths = vec![paths];
@@ -360,38 +559,25 @@ impl<'a> FSNode<'a> {
                     // recover the matched value and change the matching path accordingly,
                     // to be treated as non variable path
                     let matching_ref: &MPMatching = unsafe { mem::transmute(&matching) };
-                    let new_path = path.substitute(matching_ref);
-                    let new_path_ref = unsafe { mem::transmute(&new_path) };
-                    subs_path = Some(new_path_ref);
+                    subs_path = Some(path.substitute(matching_ref));
                 }
             }
-            let next: Option<&FSNode>;
-            let new_path: &MPPath;
-            if subs_path.is_some() {
-                new_path = subs_path.unwrap();
-            } else {
-                new_path = path;
-            }
-            if new_path.value.in_var_range {
-                next = self.get_lchild_r(new_path);
+            let new_path = subs_path.as_ref().unwrap_or(path);
+            let next = if new_path.value.in_var_range {
+                self.get_lchild_r(new_path)
             } else {
-                next = self.get_child_r(new_path);
-            }
-            if next.is_some() {
-                let next_node = next.unwrap();
+                self.get_child_r(new_path)
+            };
+            if let Some(next_node) = next {
                 let mut npaths = vec![paths];
                 npaths.extend_from_slice(new_all_all);
                 let qpaths: &[&[MPPath]] = unsafe { mem::transmute(npaths.as_slice()) };
                 resp = next_node.query_paths(qpaths, matching, resp, Some(rroot));
             }
-        } else {
-            if new_all_all.len() > 0 {
-                resp = rroot.query_paths(new_all_all, matching, resp, None);
-            } else {
-                if self.children.get().is_none() && self.lchildren.get().is_none() {
-                    resp.push(matching);
-                }
-            }
+        } else if !new_all_all.is_empty() {
+            resp = rroot.query_paths(new_all_all, matching, resp, None);
+        } else if self.template() != 0 || self.is_terminal() {
+            resp.push(matching);
         }
         resp
     }
@@ -0,0 +1,111 @@
+// Copyright (c) 2020 by Enrique Pérez Arnaud <enrique at cazalla.net>
+//
+// This file is part of the modus_ponens project.
+// http://www.modus_ponens.net
+//
+// The modus_ponens project is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// The modus_ponens project is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with any part of the modus_ponens project.
+// If not, see <http://www.gnu.org/licenses/>.
+
+//! The hashing used by the maps that make up a db.
+//!
+//! By default uindex uses 64 bit FNV-1a, which is fully specified, so that
+//! hashes, and therefore the order in which the maps are iterated (and
+//! answers returned), are the same on every platform and with every version
+//! of Rust. With the `fxhash` or the `ahash` features, the (faster) hashers
+//! from those crates are used instead. Both are deterministic for a given seed,
+//! but their output is only specified for a given version of each crate,
+//! and in the case of aHash, for a given target CPU. The hasher is chosen for
+//! the whole build, so the two features cannot be enabled together.
+
+use std::collections::{HashMap, HashSet};
+use std::hash::{BuildHasher, Hasher};
+
+#[cfg(all(feature = "fxhash", feature = "ahash"))]
+compile_error!("the `fxhash` and `ahash` features each choose the hasher of uindex; enable only one");
+
+#[cfg(not(any(feature = "fxhash", feature = "ahash")))]
+pub type MPHasher = FnvHasher;
+
+#[cfg(feature = "fxhash")]
+pub type MPHasher = fxhash::FxHasher64;
+
+#[cfg(all(feature = "ahash", not(feature = "fxhash")))]
+pub type MPHasher = ahash::AHasher;
+
+pub type MPHashMap<K, V> = HashMap<K, V, MPBuildHasher>;
+pub type MPHashSet<V> = HashSet<V, MPBuildHasher>;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// 64 bit FNV-1a.
+#[derive(Debug, Clone, Copy)]
+pub struct FnvHasher(u64);
+
+impl FnvHasher {
+    pub fn with_seed(seed: u64) -> FnvHasher {
+        FnvHasher(FNV_OFFSET_BASIS ^ seed)
+    }
+}
+
+impl Default for FnvHasher {
+    fn default() -> FnvHasher {
+        FnvHasher::with_seed(0)
+    }
+}
+
+impl Hasher for FnvHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(FNV_PRIME);
+        }
+    }
+}
+
+/// Builds the hashers for the maps in a db, all with the same seed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MPBuildHasher {
+    pub seed: u64,
+}
+
+impl MPBuildHasher {
+    pub fn with_seed(seed: u64) -> MPBuildHasher {
+        MPBuildHasher { seed }
+    }
+}
+
+impl BuildHasher for MPBuildHasher {
+    type Hasher = MPHasher;
+
+    #[cfg(not(any(feature = "fxhash", feature = "ahash")))]
+    fn build_hasher(&self) -> MPHasher {
+        FnvHasher::with_seed(self.seed)
+    }
+
+    #[cfg(feature = "fxhash")]
+    fn build_hasher(&self) -> MPHasher {
+        let mut hasher = fxhash::FxHasher64::default();
+        hasher.write_u64(self.seed);
+        hasher
+    }
+
+    #[cfg(all(feature = "ahash", not(feature = "fxhash")))]
+    fn build_hasher(&self) -> MPHasher {
+        ahash::AHasher::new_with_keys(self.seed as u128, self.seed as u128)
+    }
+}
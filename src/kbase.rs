@@ -17,14 +17,67 @@
 // along with any part of the modus_ponens project.
 // If not, see <http://www.gnu.org/licenses/>.
 
+use std::collections::BTreeMap;
+
+use crate::hashing::MPBuildHasher;
 use crate::matching::MPMatching;
 
+/// What telling a sentence to a db did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TellStatus {
+    /// The sentence was added.
+    Added,
+    /// The sentence was already in the db.
+    Present,
+    /// The sentence was added, replacing the values of some unique (`v_u_`)
+    /// productions in sentences already in the db.
+    Replaced,
+}
+
+/// Identifies a sentence in a db. The id is a 64 bit FNV-1a hash of the
+/// names of the productions in the sentence, the text of its leaves and
+/// the literal text between them, ignoring whitespace. So the same sentence,
+/// however it is formatted, has the same id in every db that holds it,
+/// in every process, and keeps it when a db is dumped and loaded back.
+/// A sentence whose hash is already the id of another sentence in the db
+/// takes the next free id instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FactId(pub u64);
+
+/// How many sentences there are in a db, in total and by the production
+/// at the top of their parse trees.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Stats {
+    pub sentences: usize,
+    pub productions: BTreeMap<String, usize>,
+}
+
+/// The id of a sentence told to a db, and what was done with it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Told {
+    pub id: FactId,
+    pub status: TellStatus,
+}
+
+/// A db of sentences. The knowledge given to `tell` and `ask` only has to live
+/// for the call, not as long as the db: the text of each segment of a sentence
+/// is copied into the lexicon of the db, and neither the db nor the answers it
+/// gives keep any reference to the knowledge, so it can be a temporary `String`.
 pub trait DataBase<'a> {
-    fn tell(&'a self, knowledge: &'a str);
-    fn ask(&'a self, knowledge: &'a str) -> Vec<MPMatching<'a>>;
+    /// Adds the sentences in the knowledge to the db,
+    /// returning what was done with each of them, in order.
+    fn tell(&'a self, knowledge: &str) -> Vec<Told>;
+    fn ask(&'a self, knowledge: &str) -> Vec<MPMatching<'a>>;
+    /// The text of the sentence with the given id, if it is in the db.
+    fn get(&'a self, id: FactId) -> Option<String>;
+    /// Removes the sentence with the given id from the db.
+    /// Returns whether it was in the db.
+    fn forget(&'a self, id: FactId) -> bool;
 }
 
 pub trait DBGen<'a> {
     type Output: DataBase<'a>;
     fn gen_db() -> Self::Output;
+    fn gen_db_with_hasher(hasher: MPBuildHasher) -> Self::Output;
 }
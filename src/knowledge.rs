@@ -23,45 +23,108 @@ extern crate syn;
 
 use proc_macro2::TokenStream;
 
+use crate::kparser;
+
 pub fn derive_db() -> TokenStream {
     quote! {
 
-        pub struct DB<'a> {
-            mpparser: MPParser,
-            facts: FactSet<'a>,
+        pub type DB<'a> = MPDB<'a, MPParser>;
+    }
+}
+
+/// The grammar for knowledge with the syntax given in the `terminator`,
+/// `comment`, `whitespace` and `string` attributes, or `None` if there are none.
+/// A `string` attribute without a value gives double quoted strings.
+pub fn knowledge_grammar(attrs: &[syn::Attribute]) -> syn::Result<Option<String>> {
+    let mut terminators: Vec<String> = vec![];
+    let mut comment: Option<String> = None;
+    let mut whitespace: Option<String> = None;
+    let mut string: Option<String> = None;
+    for attr in attrs {
+        if attr.path.is_ident("terminator") {
+            terminators.push(string_value(attr)?);
+        } else if attr.path.is_ident("comment") {
+            comment = Some(string_value(attr)?);
+        } else if attr.path.is_ident("whitespace") {
+            whitespace = Some(string_value(attr)?);
+        } else if attr.path.is_ident("string") {
+            string = Some(match attr.parse_meta() {
+                Ok(syn::Meta::Path(_)) => kparser::DEFAULT_STRING.to_string(),
+                _ => string_value(attr)?,
+            });
         }
-        impl<'a> DataBase<'a> for DB<'a> {
-            fn tell(&'a self, knowledge: &'a str) {
-                let result = self.mpparser.parse_text(knowledge.trim());
-                if result.is_err() {
-                    panic!("Parsing problem! {}", result.err().unwrap());
-                } else {
-                    let ParseResult { facts } = result.ok().unwrap();
-                    for fact in facts {
+    }
+    if terminators.is_empty() && comment.is_none() && whitespace.is_none() && string.is_none() {
+        return Ok(None);
+    }
+    Ok(Some(kparser::knowledge_grammar(
+        &terminators,
+        comment.as_deref(),
+        whitespace.as_deref(),
+        string.as_deref(),
+    )))
+}
 
-                        let fact_paths = self.mpparser.parse_fact(fact);
-                        let (exists, paths) = self.facts.ask_fact_bool(fact_paths);
-                        if  exists {
-                            return;
-                        }
-                        self.facts.add_fact(paths);
-                    }
-                }
+/// A parser for knowledge with the syntax given in the `terminator`,
+/// `comment`, `whitespace` and `string` attributes, if any, along with the
+/// `parse_text` method that uses it, and the `terminator` and `terminators`
+/// methods, that give the first of the terminators and all of them.
+pub fn derive_kparser(attrs: &[syn::Attribute]) -> syn::Result<(TokenStream, TokenStream)> {
+    let grammar = match knowledge_grammar(attrs)? {
+        Some(grammar) => grammar,
+        None => return Ok((quote! {}, quote! {})),
+    };
+    let terminators: Vec<String> = attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("terminator"))
+        .map(string_value)
+        .collect::<syn::Result<_>>()?;
+    let terminator = terminators.first().map(|terminator| {
+        quote! {
+
+            fn terminator(&self) -> &'static str {
+                #terminator
             }
-            fn ask(&'a self, knowledge: &'a str) -> Vec<MPMatching<'a>> {
-                let ParseResult { mut facts, .. } = self.mpparser.parse_text(knowledge).ok().expect("parse result");
-                let q = facts.iter().map(|fact| self.mpparser.parse_fact(fact)).collect();
-                self.facts.ask_facts(q)
+
+            fn terminators(&self) -> &'static [&'static str] {
+                &[#(#terminators),*]
             }
         }
-        impl<'a> DB<'a> {
+    });
+    let derived_kparser = quote! {
 
-            pub fn new () -> DB<'a> {
-                Self {
-                    mpparser: MPParser::new(),
-                    facts: FactSet::new(),
-                }
+        mod knowledge_syntax {
+            use super::*;
+
+            #[derive(Parser)]
+            #[grammar_inline = #grammar]
+            pub struct KParser;
+        }
+
+        impl RuleName for knowledge_syntax::Rule {
+            fn rule_name(&self) -> String {
+                format!("{:?}", self)
+            }
+        }
+    };
+    let parse_text = quote! {
+
+            fn parse_text(&'a self, text: &'a str) -> Result<ParseResult<'a>, Error<kparser::Rule>> {
+                kparser::parse_knowledge::<knowledge_syntax::KParser, _>(knowledge_syntax::Rule::knowledge, text)
             }
+            #terminator
+    };
+    Ok((derived_kparser, parse_text))
+}
+
+/// The string value of an attribute like `#[terminator = ";"]`.
+pub fn string_value(attr: &syn::Attribute) -> syn::Result<String> {
+    match attr.parse_meta() {
+        Ok(syn::Meta::NameValue(syn::MetaNameValue { lit: syn::Lit::Str(value), .. })) => Ok(value.value()),
+        _ => {
+            let path = &attr.path;
+            let message = format!("expected a string value for the #[{}] attribute", quote! { #path });
+            Err(syn::Error::new_spanned(attr, message))
         }
     }
 }
@@ -1,3 +1,158 @@
+use std::cmp::Reverse;
+
+use pest::error::{Error, ErrorVariant, InputLocation};
+use pest::iterators::Pairs;
+use pest::{Parser, Position, RuleType, Span};
+
+use crate::parse_result::ParseResult;
+use crate::sentence::RuleName;
+
 #[derive(Parser)]
 #[grammar = "knowledge.pest"]
 pub struct KParser;
+
+pub const DEFAULT_TERMINATORS: [&str; 2] = ["<>", "◊"];
+pub const DEFAULT_COMMENT: &str = r#""/*" ~ (!"*/" ~ ANY)* ~ "*/""#;
+pub const DEFAULT_WHITESPACE: &str = r#"" " | "\t" | NEWLINE"#;
+pub const DEFAULT_STRING: &str = r#""\"" ~ ("\\" ~ ANY | !"\"" ~ ANY)* ~ "\"""#;
+
+impl RuleName for Rule {
+    fn rule_name(&self) -> String {
+        format!("{:?}", self)
+    }
+}
+
+/// A pest grammar for knowledge, equivalent to the one in `knowledge.pest`,
+/// in which sentences are terminated by any of the given terminators,
+/// and with the given pest expressions for comments, whitespace and strings.
+/// Empty sentences (e.g. blank lines when sentences are terminated by newlines)
+/// are ignored, and the terminator of the last sentence can be omitted.
+/// With a pest expression for strings, terminators within strings do not end
+/// sentences; without one, there are no strings. Terminators that are also
+/// whitespace (e.g. newlines) are not skipped as such, neither between nor,
+/// since sentences are compound atomic, within sentences.
+pub fn knowledge_grammar(
+    terminators: &[String],
+    comment: Option<&str>,
+    whitespace: Option<&str>,
+    string: Option<&str>,
+) -> String {
+    let mut terminators: Vec<&str> = if terminators.is_empty() {
+        DEFAULT_TERMINATORS.to_vec()
+    } else {
+        terminators.iter().map(|t| t.as_str()).collect()
+    };
+    terminators.sort_by_key(|t| Reverse(t.len()));
+    let terminator = terminators
+        .iter()
+        .map(|t| pest_string(t))
+        .collect::<Vec<String>>()
+        .join(" | ");
+    let (fact, string) = match string {
+        Some(string) => (
+            format!("(string | !({}) ~ ANY)+", terminator),
+            format!("string = @{{ {} }}\n", string),
+        ),
+        None => (format!("(!({}) ~ ANY)+", terminator), String::new()),
+    };
+    format!(
+        "knowledge = _{{ SOI ~ ({t})* ~ rknowledge ~ EOI }}\n\
+         rknowledge = {{ (fact ~ (({t})+ | &EOI))+ }}\n\
+         fact = ${{ {f} }}\n\
+         {s}\
+         COMMENT = _{{ {c} }}\n\
+         WHITESPACE = _{{ !({t}) ~ ({w}) }}\n",
+        t = terminator,
+        f = fact,
+        s = string,
+        c = comment.unwrap_or(DEFAULT_COMMENT),
+        w = whitespace.unwrap_or(DEFAULT_WHITESPACE),
+    )
+}
+
+fn pest_string(text: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Splits knowledge into sentences, with a parser for a grammar obtained
+/// from `knowledge_grammar`. Errors are reported in terms of `Rule`.
+pub fn parse_knowledge<'a, P, R>(rule: R, text: &'a str) -> Result<ParseResult<'a>, Error<Rule>>
+where
+    P: Parser<R>,
+    R: RuleType + RuleName,
+{
+    read_knowledge(P::parse(rule, text), text)
+}
+
+/// Like `parse_knowledge`, with the result of parsing the text with a grammar
+/// obtained from `knowledge_grammar` by other means, e.g. with pest's VM.
+pub fn read_knowledge<'a, R>(
+    parse_tree: Result<Pairs<'a, R>, Error<R>>,
+    text: &'a str,
+) -> Result<ParseResult<'a>, Error<Rule>>
+where
+    R: RuleType + RuleName,
+{
+    let parse_tree = parse_tree.map_err(|e| rename_error(e, text))?;
+    let mut facts: Vec<&'a str> = vec![];
+    // leading whitespace, if not silent, comes before the sentences
+    for pair in parse_tree.flatten() {
+        if pair.as_rule().rule_name() == "fact" {
+            // atomic sentences keep the whitespace before their terminator
+            facts.push(pair.as_str().trim_end());
+        }
+    }
+    Ok(ParseResult { facts })
+}
+
+fn rename_error<R: RuleType + RuleName>(error: Error<R>, text: &str) -> Error<Rule> {
+    let rename = |rules: Vec<R>| -> Vec<Rule> {
+        rules
+            .iter()
+            .filter_map(|r| rule_named(&r.rule_name()))
+            .collect()
+    };
+    let variant = match error.variant {
+        ErrorVariant::ParsingError {
+            positives,
+            negatives,
+        } => ErrorVariant::ParsingError {
+            positives: rename(positives),
+            negatives: rename(negatives),
+        },
+        ErrorVariant::CustomError { message } => ErrorVariant::CustomError { message },
+    };
+    match error.location {
+        InputLocation::Pos(pos) => {
+            Error::new_from_pos(variant, Position::new(text, pos).expect("error position"))
+        }
+        InputLocation::Span((start, end)) => {
+            Error::new_from_span(variant, Span::new(text, start, end).expect("error span"))
+        }
+    }
+}
+
+fn rule_named(name: &str) -> Option<Rule> {
+    match name {
+        "knowledge" => Some(Rule::knowledge),
+        "rknowledge" => Some(Rule::rknowledge),
+        "fact" => Some(Rule::fact),
+        "string" => Some(Rule::string),
+        "COMMENT" => Some(Rule::COMMENT),
+        "WHITESPACE" => Some(Rule::WHITESPACE),
+        "EOI" => Some(Rule::EOI),
+        _ => None,
+    }
+}
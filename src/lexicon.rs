@@ -17,20 +17,134 @@
 // along with any part of the modus_ponens project.
 // If not, see <http://www.gnu.org/licenses/>.
 
-use std::{cell::RefCell, collections::HashMap, mem};
+use std::{
+    cell::{Cell, RefCell},
+    mem,
+};
 
+use crate::hashing::{MPBuildHasher, MPHashMap};
 use crate::segment::MPSegment;
 
+/// Interns the strings, keys and segments that make up the paths in a db,
+/// handing out ids that are unique within the lexicon. Ids are taken from a
+/// single counter, so an id minted for a name can never be mistaken for an id
+/// minted for a key; 0 is never handed out, and can be used as the empty key.
 pub struct Lexicon {
-    segments: RefCell<HashMap<u64, Box<MPSegment>>>,
+    hasher: MPBuildHasher,
+    last_id: Cell<u64>,
+    names: RefCell<MPHashMap<String, u64>>,
+    rule_names: RefCell<MPHashMap<u64, String>>,
+    keys: RefCell<MPHashMap<Vec<u64>, u64>>,
+    segments: RefCell<MPHashMap<u64, Box<MPSegment>>>,
+    templates: RefCell<MPHashMap<(u64, Vec<String>), u64>>,
+    template_parts: RefCell<MPHashMap<u64, (u64, Vec<String>)>>,
+}
+
+impl Default for Lexicon {
+    fn default() -> Self {
+        Lexicon::new()
+    }
 }
 
 impl Lexicon {
     pub fn new() -> Self {
+        Lexicon::with_hasher(MPBuildHasher::default())
+    }
+    pub fn with_hasher(hasher: MPBuildHasher) -> Self {
         Lexicon {
-            segments: RefCell::new(HashMap::new()),
+            hasher,
+            last_id: Cell::new(0),
+            names: RefCell::new(MPHashMap::with_hasher(hasher)),
+            rule_names: RefCell::new(MPHashMap::with_hasher(hasher)),
+            keys: RefCell::new(MPHashMap::with_hasher(hasher)),
+            segments: RefCell::new(MPHashMap::with_hasher(hasher)),
+            templates: RefCell::new(MPHashMap::with_hasher(hasher)),
+            template_parts: RefCell::new(MPHashMap::with_hasher(hasher)),
+        }
+    }
+    pub fn hasher(&self) -> MPBuildHasher {
+        self.hasher
+    }
+    fn next_id(&self) -> u64 {
+        let id = self.last_id.get() + 1;
+        self.last_id.set(id);
+        id
+    }
+    /// Id for a string, be it the name of a production or some text.
+    pub fn intern_name(&self, name: &str) -> u64 {
+        let mut map = self.names.borrow_mut();
+        if let Some(id) = map.get(name) {
+            return *id;
         }
+        let id = self.next_id();
+        map.insert(name.to_string(), id);
+        id
+    }
+    /// Id for the name of a production, which can be given back by `rule_name`.
+    pub fn intern_rule_name(&self, name: &str) -> u64 {
+        let id = self.intern_name(name);
+        self.rule_names
+            .borrow_mut()
+            .entry(id)
+            .or_insert_with(|| name.to_string());
+        id
+    }
+    /// The name of the production with the given id.
+    pub fn rule_name(&self, id: u64) -> Option<&str> {
+        let map = self.rule_names.borrow();
+        let name = map.get(&id)?;
+        // SAFETY: the name is only borrowed from the map for as long as the
+        // lexicon is. Names are never removed from the map nor changed, and
+        // their text stays where it is in the heap when the map grows.
+        Some(unsafe { mem::transmute::<&str, &str>(name.as_str()) })
     }
+    /// Id for a sequence of ids.
+    pub fn intern_key(&self, parts: &[u64]) -> u64 {
+        let mut map = self.keys.borrow_mut();
+        if let Some(id) = map.get(parts) {
+            return *id;
+        }
+        let id = self.next_id();
+        map.insert(parts.to_vec(), id);
+        id
+    }
+    /// Id for the template of a sentence: the name of the production at the top
+    /// of its parse tree, and the literal text before, between and after the
+    /// values of its leaves, so that there is one more gap than there are values.
+    pub fn intern_template(&self, production: u64, gaps: Vec<String>) -> u64 {
+        let mut map = self.templates.borrow_mut();
+        let template = (production, gaps);
+        if let Some(id) = map.get(&template) {
+            return *id;
+        }
+        let id = self.next_id();
+        self.template_parts
+            .borrow_mut()
+            .insert(id, template.clone());
+        map.insert(template, id);
+        id
+    }
+    /// The name of the production at the top of the sentences with the template.
+    pub fn template_production(&self, id: u64) -> Option<u64> {
+        let map = self.template_parts.borrow();
+        map.get(&id).map(|(production, _)| *production)
+    }
+    /// The text of the sentence with the template and the values of its leaves.
+    pub fn fill_template(&self, id: u64, values: &[&str]) -> Option<String> {
+        let map = self.template_parts.borrow();
+        let (_, gaps) = map.get(&id)?;
+        let mut text = String::new();
+        for (index, gap) in gaps.iter().enumerate() {
+            if index > 0 {
+                text.push_str(values.get(index - 1)?);
+            }
+            text.push_str(gap);
+        }
+        Some(text)
+    }
+    /// Segment for the given key, which must have been obtained from
+    /// `intern_key` and must identify the segment by name and text.
+    #[allow(clippy::too_many_arguments)]
     pub fn intern_with_name(
         &self,
         name: u64,
@@ -44,8 +158,9 @@ impl Lexicon {
     ) -> &MPSegment {
         let mut map = self.segments.borrow_mut();
 
-        if !map.contains_key(&key) {
-            let segment = MPSegment::new(
+        let interned = map.entry(key).or_insert_with(|| {
+            Box::new(MPSegment::new(
+                key,
                 name,
                 text.to_string(),
                 is_leaf,
@@ -53,11 +168,8 @@ impl Lexicon {
                 in_var_range,
                 is_empty,
                 unique,
-            );
-            map.insert(key, Box::new(segment));
-        }
-
-        let interned = map.get(&key).unwrap();
+            ))
+        });
 
         unsafe { mem::transmute(interned.as_ref()) }
     }
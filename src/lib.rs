@@ -39,26 +39,39 @@
 //! Check out the [README](https://gitlab.com/enriquepablo/uindex) for more detailed info.
 //!
 
-#![feature(hash_set_entry)]
-#![feature(once_cell)]
 #![allow(dead_code)]
 
+pub mod bindings;
 pub mod constants;
+pub mod hashing;
 pub mod matching;
 pub mod path;
 pub mod segment;
 //pub mod fact;
+pub mod db;
+pub mod dynamic;
 pub mod facttree;
 pub mod kbase;
 mod knowledge;
 pub mod kparser;
 pub mod lexicon;
+mod macros;
 pub mod parse_result;
 mod parser;
+pub mod schema;
+pub mod sentence;
+pub mod stream;
+pub mod typed;
+mod typed_derive;
 
 extern crate pest;
 #[macro_use]
 extern crate pest_derive;
+extern crate pest_meta;
+extern crate pest_vm;
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde;
 
 extern crate proc_macro;
 extern crate proc_macro2;
@@ -71,45 +84,124 @@ use proc_macro2::TokenStream;
 pub fn derive_dbase(input: proc_macro2::TokenStream) -> TokenStream {
     let ast: syn::DeriveInput = syn::parse2(input).unwrap();
     let name = ast.ident;
-    let attr = &ast.attrs[0];
+    let vis = ast.vis;
+    let attr = match ast
+        .attrs
+        .iter()
+        .find(|attr| attr.path.is_ident("grammar") || attr.path.is_ident("grammar_inline"))
+    {
+        Some(attr) => attr,
+        None => {
+            return syn::Error::new_spanned(
+                &name,
+                "expected a #[grammar = \"...\"] or #[grammar_inline = \"...\"] attribute",
+            )
+            .to_compile_error()
+        }
+    };
+    let (grammar, grammar_source) = match parser::grammar_source(attr) {
+        Ok(source) => source,
+        Err(e) => return e.to_compile_error(),
+    };
+    let (var_names, var_names_method) = match parser::derive_var_names(&ast.attrs) {
+        Ok(derived) => derived,
+        Err(e) => return e.to_compile_error(),
+    };
+    if let Err(errors) = parser::check_grammar(attr, &grammar, &var_names) {
+        return errors;
+    }
+
+    // Everything is generated in a module of its own, so that several
+    // dbs can be derived in the same module; only the db type is exported.
+    let module = format_ident!("__uindex_{}", name);
+    let db_name = format_ident!("{}DB", name);
 
-    let derived_parser = parser::derive_parser(attr);
+    let (derived_kparser, parse_text) = match knowledge::derive_kparser(&ast.attrs) {
+        Ok(derived) => derived,
+        Err(e) => return e.to_compile_error(),
+    };
+    let derived_parser = parser::derive_parser(attr, grammar_source, parse_text, var_names_method);
     let derived_db = knowledge::derive_db();
+    let (derived_macro, macro_export) = macros::derive_sentences_macro(&name, &module, &ast.attrs);
 
     quote! {
 
-        use std::collections::{ HashMap, HashSet, VecDeque };
-        use std::cell::RefCell;
-        use std::mem;
+        #[doc(hidden)]
+        #[allow(non_snake_case)]
+        pub mod #module {
 
-        use std::collections::hash_map::DefaultHasher;
-        use std::hash::{Hash, Hasher};
+            use std::collections::{ HashMap, HashSet, VecDeque };
 
-        use log::{info, trace};
+            use pest::error::Error;
+            use pest::Parser;
+            use pest::iterators::Pair;
+            use uindex::db::MPDB;
+            use uindex::hashing::MPBuildHasher;
+            use uindex::kbase::{ DataBase, DBGen };
+            use uindex::kparser;
+            use uindex::lexicon::Lexicon;
+            use uindex::parse_result::ParseResult;
+            use uindex::sentence::{ RuleName, SentenceParser, VarNames };
 
-        use pest::error::Error;
-        use pest::Parser;
-        use pest::iterators::Pair;
-        use uindex::constants;
-        use uindex::facttree::FactSet;
-        use uindex::kbase::{ DataBase, DBGen };
-        use uindex::lexicon::Lexicon;
-        use uindex::matching::{ MPMatching };
-        use uindex::path::{ TSegment, MPPath };
-        use uindex::segment::MPSegment;
-        use uindex::kparser;
-        use uindex::parse_result::ParseResult;
 
+            #derived_kparser
 
-        #derived_parser
+            #derived_parser
 
-        #derived_db
+            #derived_db
 
-        impl<'a> DBGen<'a> for #name {
-            type Output = DB<'a>;
-            fn gen_db() -> DB<'a> {
-                DB::new()
+            #derived_macro
+
+            impl<'a> DBGen<'a> for super::#name {
+                type Output = DB<'a>;
+                fn gen_db() -> DB<'a> {
+                    MPDB::new(MPParser::new())
+                }
+                fn gen_db_with_hasher(hasher: MPBuildHasher) -> DB<'a> {
+                    MPDB::new(MPParser::with_hasher(hasher))
+                }
             }
         }
+
+        #vis type #db_name<'a> = #module::DB<'a>;
+
+        #macro_export
     }
 }
+
+pub fn derive_sentence(input: proc_macro2::TokenStream) -> TokenStream {
+    typed_derive::derive_sentence(input)
+}
+
+pub fn check_sentences(input: proc_macro2::TokenStream) -> TokenStream {
+    macros::check_sentences(input)
+}
+
+/// Checks, at compile time, that the given text holds facts that can be
+/// parsed with the grammar of a derived db generator, and expands to the text.
+///
+/// ```ignore
+/// db.tell(fact!(DBGenerator, "susan likes oranges ◊"));
+/// ```
+#[macro_export]
+macro_rules! fact {
+    ($($gen:ident)::+, $text:literal) => {
+        $($gen)::+!(fact $text)
+    };
+}
+
+/// Checks, at compile time, that the given text holds a query that can be
+/// parsed with the grammar of a derived db generator, and expands to a prepared
+/// query, whose answers have a `String` field for each variable in the query.
+///
+/// ```ignore
+/// for answer in query!(DBGenerator, "susan likes X1 ◊").ask(&db) {
+///     println!("{}", answer.X1);
+/// }
+/// ```
+#[macro_export]
+macro_rules! query {
+    ($($gen:ident)::+, $text:literal) => {
+        $($gen)::+!(query $text)
+    };
+}
@@ -0,0 +1,241 @@
+// Copyright (c) 2020 by Enrique Pérez Arnaud <enrique at cazalla.net>
+//
+// This file is part of the modus_ponens project.
+// http://www.modus_ponens.net
+//
+// The modus_ponens project is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// The modus_ponens project is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with any part of the modus_ponens project.
+// If not, see <http://www.gnu.org/licenses/>.
+
+//! Sentences that are checked against the grammar of a db at compile time.
+
+extern crate proc_macro2;
+extern crate syn;
+
+use pest::iterators::Pair;
+use pest::Parser;
+use pest_meta::optimizer;
+use pest_vm::Vm;
+use proc_macro2::TokenStream;
+use syn::parse::{Parse, ParseStream};
+
+use crate::dynamic::DynamicParser;
+use crate::hashing::MPBuildHasher;
+use crate::knowledge;
+use crate::kparser;
+use crate::parser;
+use crate::schema::parse_grammar;
+use crate::sentence::{SentenceParser, VarNames};
+
+/// A macro named as the derived struct, that passes the sentences it is given,
+/// along with the attributes of the derive, to `check_sentences`.
+pub fn derive_sentences_macro(
+    name: &syn::Ident,
+    module: &syn::Ident,
+    attrs: &[syn::Attribute],
+) -> (TokenStream, TokenStream) {
+    let attrs: Vec<&syn::Attribute> = attrs
+        .iter()
+        .filter(|attr| {
+            attr.path
+                .get_ident()
+                .is_some_and(|ident| ATTRIBUTES.contains(&ident.to_string().as_str()))
+        })
+        .collect();
+    let derived_macro = quote! {
+
+        #[allow(unused_macros)]
+        macro_rules! #name {
+            ($kind:ident $text:literal) => {
+                ::uindex_derive::__check_sentences!(#(#attrs)* $kind $text)
+            };
+        }
+
+        #[allow(unused_imports)]
+        pub(crate) use #name;
+    };
+    let export = quote! {
+        #[allow(unused_imports)]
+        pub(crate) use #module::#name;
+    };
+    (derived_macro, export)
+}
+
+const ATTRIBUTES: [&str; 9] = [
+    "grammar",
+    "grammar_inline",
+    "terminator",
+    "comment",
+    "whitespace",
+    "string",
+    "var_rule",
+    "var_range_prefix",
+    "unique_prefix",
+];
+
+struct SentencesInput {
+    attrs: Vec<syn::Attribute>,
+    kind: syn::Ident,
+    text: syn::LitStr,
+}
+
+impl Parse for SentencesInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        Ok(SentencesInput {
+            attrs: input.call(syn::Attribute::parse_outer)?,
+            kind: input.parse()?,
+            text: input.parse()?,
+        })
+    }
+}
+
+/// Parses the text given to the `fact!` or `query!` macros with the grammar
+/// given in the attributes of the derive, and expands to the text, for `fact!`,
+/// or to a prepared query, for `query!`.
+pub fn check_sentences(input: TokenStream) -> TokenStream {
+    let SentencesInput { attrs, kind, text } = match syn::parse2(input) {
+        Ok(input) => input,
+        Err(e) => return e.to_compile_error(),
+    };
+    match check(&attrs, &kind, &text) {
+        Ok(expanded) => expanded,
+        Err(e) => e.to_compile_error(),
+    }
+}
+
+fn check(
+    attrs: &[syn::Attribute],
+    kind: &syn::Ident,
+    text: &syn::LitStr,
+) -> syn::Result<TokenStream> {
+    let attr = attrs
+        .iter()
+        .find(|attr| attr.path.is_ident("grammar") || attr.path.is_ident("grammar_inline"))
+        .ok_or_else(|| syn::Error::new_spanned(kind, "no grammar for the sentences"))?;
+    let (grammar, _) = parser::grammar_source(attr)?;
+    let (var_names, _) = parser::derive_var_names(attrs)?;
+    let knowledge = knowledge::knowledge_grammar(attrs)?;
+    let value = text.value();
+    let facts = split_knowledge(knowledge.as_deref(), &value).map_err(|e| {
+        syn::Error::new_spanned(
+            text,
+            format!("cannot split `{}` into sentences:\n{}", value, e),
+        )
+    })?;
+    let fact_parser =
+        DynamicParser::from_grammar_with_var_names(&grammar, var_names, MPBuildHasher::default())
+            .map_err(|_| syn::Error::new_spanned(attr, "invalid grammar"))?;
+    let mut vars: Vec<String> = vec![];
+    for fact in facts {
+        let tree = fact_parser.parse_tree(fact).map_err(|e| {
+            syn::Error::new_spanned(text, format!("cannot parse `{}`:\n{}", fact, e))
+        })?;
+        collect_vars(tree, &var_names, &mut vars);
+    }
+    if kind == "fact" {
+        if !vars.is_empty() {
+            return Err(syn::Error::new_spanned(
+                text,
+                format!("facts cannot have variables, found {}", vars.join(", ")),
+            ));
+        }
+        Ok(quote! { #text })
+    } else if kind == "query" {
+        query(text, &vars)
+    } else {
+        Err(syn::Error::new_spanned(kind, "expected `fact` or `query`"))
+    }
+}
+
+/// Splits the text into sentences, with the grammar for the knowledge syntax
+/// given in the attributes, if any.
+fn split_knowledge<'t>(knowledge: Option<&str>, text: &'t str) -> Result<Vec<&'t str>, String> {
+    let knowledge = match knowledge {
+        None => kparser::KParser::parse(kparser::Rule::knowledge, text)
+            .map_err(|e| e.to_string())?
+            .flatten()
+            .filter(|pair| pair.as_rule() == kparser::Rule::fact)
+            .map(|pair| pair.as_str())
+            .collect(),
+        Some(grammar) => {
+            let ast = parse_grammar(grammar).map_err(|_| "invalid knowledge syntax".to_string())?;
+            let vm = Vm::new(optimizer::optimize(ast));
+            let spans: Vec<(usize, usize)> = vm
+                .parse("knowledge", text)
+                .map_err(|e| e.to_string())?
+                .flatten()
+                .filter(|pair| pair.as_rule() == "fact")
+                .map(|pair| (pair.as_span().start(), pair.as_span().end()))
+                .collect();
+            spans
+                .into_iter()
+                .map(|(start, end)| text[start..end].trim_end())
+                .collect()
+        }
+    };
+    Ok(knowledge)
+}
+
+fn collect_vars<'t>(tree: Pair<'t, &'t str>, var_names: &VarNames, vars: &mut Vec<String>) {
+    for pair in tree.into_inner().flatten() {
+        let name = pair.as_str().to_string();
+        if var_names.is_var(pair.as_rule()) && !vars.contains(&name) {
+            vars.push(name);
+        }
+    }
+}
+
+/// A prepared query, whose answers have a field for each variable in the query.
+fn query(text: &syn::LitStr, vars: &[String]) -> syn::Result<TokenStream> {
+    let fields = vars
+        .iter()
+        .map(|var| {
+            syn::parse_str::<syn::Ident>(var).map_err(|_| {
+                syn::Error::new_spanned(text, format!("variable `{}` cannot name a field", var))
+            })
+        })
+        .collect::<syn::Result<Vec<syn::Ident>>>()?;
+    Ok(quote! {
+        {
+            #[allow(non_snake_case)]
+            #[derive(Debug, Clone, PartialEq, Eq)]
+            struct Answer {
+                #(pub #fields: String,)*
+            }
+
+            #[derive(Debug, Clone, Copy)]
+            struct Query;
+
+            impl Query {
+                pub const TEXT: &'static str = #text;
+
+                pub fn text(&self) -> &'static str {
+                    Self::TEXT
+                }
+
+                pub fn ask<'a, D: ::uindex::kbase::DataBase<'a>>(&self, db: &'a D) -> Vec<Answer> {
+                    db.ask(Self::TEXT)
+                        .iter()
+                        .map(|matching| Answer {
+                            #(#fields: ::uindex::matching::binding(matching, #vars)
+                                .expect("a binding for every variable")
+                                .to_string(),)*
+                        })
+                        .collect()
+                }
+            }
+
+            Query
+        }
+    })
+}
@@ -17,15 +17,22 @@
 // along with any part of the modus_ponens project.
 // If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::HashMap;
-
+use crate::hashing::MPHashMap;
 use crate::segment::MPSegment;
 
-pub type MPMatching<'a> = HashMap<&'a MPSegment, &'a MPSegment>;
+pub type MPMatching<'a> = MPHashMap<&'a MPSegment, &'a MPSegment>;
 
 pub fn get_or_key<'a>(matching: &'a MPMatching, key: &'a MPSegment) -> &'a MPSegment {
     match matching.get(key) {
-        Some(matched) => *matched,
+        Some(matched) => matched,
         None => key,
     }
 }
+
+/// The text bound to the variable with the given text, if any.
+pub fn binding<'a>(matching: &MPMatching<'a>, var: &str) -> Option<&'a str> {
+    matching
+        .iter()
+        .find(|(key, _)| key.text == var)
+        .map(|(_, value)| value.text.as_str())
+}
@@ -24,27 +24,114 @@ extern crate syn;
 
 use proc_macro2::TokenStream;
 
-pub fn derive_parser(attr: &syn::Attribute) -> TokenStream {
-    quote! {
+use crate::knowledge::string_value;
+use crate::schema::{Schema, SchemaError};
+use crate::sentence::VarNames;
 
-        pub struct StringCache(RefCell<HashSet<String>>);
+/// The names given in the `var_rule`, `var_range_prefix` and `unique_prefix`
+/// attributes, if any, along with a `var_names` method that returns them.
+/// Without a `unique_prefix`, a renamed var range prefix followed by `u_` is used.
+pub fn derive_var_names(attrs: &[syn::Attribute]) -> syn::Result<(VarNames, TokenStream)> {
+    let mut var_names = VarNames::default();
+    let given = |name: &str| attrs.iter().find(|attr| attr.path.is_ident(name));
+    let names = ["var_rule", "var_range_prefix", "unique_prefix"];
+    if names.iter().all(|name| given(name).is_none()) {
+        return Ok((var_names, quote! {}));
+    }
+    // the derive only runs once, so the names can live as long as it does
+    let leak = |value: String| -> &'static str { Box::leak(value.into_boxed_str()) };
+    if let Some(attr) = given("var_rule") {
+        var_names.var_rule = leak(string_value(attr)?);
+    }
+    if let Some(attr) = given("var_range_prefix") {
+        var_names.var_range_prefix = leak(string_value(attr)?);
+        var_names.unique_prefix = leak(format!("{}u_", var_names.var_range_prefix));
+    }
+    if let Some(attr) = given("unique_prefix") {
+        var_names.unique_prefix = leak(string_value(attr)?);
+    }
+    let VarNames { var_rule, var_range_prefix, unique_prefix } = var_names;
+    let method = quote! {
 
-        impl StringCache {
-            fn new() -> Self {
-                StringCache(RefCell::new(HashSet::new()))
+            fn var_names(&self) -> VarNames {
+                VarNames {
+                    var_rule: #var_rule,
+                    var_range_prefix: #var_range_prefix,
+                    unique_prefix: #unique_prefix,
+                }
             }
+    };
+    Ok((var_names, method))
+}
 
-            fn intern<'a>(&'a self, s: &str) -> &'a str {
-                let mut set = self.0.borrow_mut();
-                let mut interned = set.get(s);
+/// The source of the grammar given in a `grammar` or `grammar_inline` attribute,
+/// along with an expression for it in the generated code.
+pub fn grammar_source(attr: &syn::Attribute) -> Result<(String, TokenStream), syn::Error> {
+    let value = match attr.parse_meta() {
+        Ok(syn::Meta::NameValue(syn::MetaNameValue { lit: syn::Lit::Str(value), .. })) => value.value(),
+        _ => return Err(syn::Error::new_spanned(
+            attr,
+            "expected a grammar attribute like #[grammar = \"fact.pest\"]",
+        )),
+    };
+    if attr.path.is_ident("grammar_inline") {
+        return Ok((value.clone(), quote! { #value }));
+    }
+    let root = std::env::var("CARGO_MANIFEST_DIR").unwrap_or_else(|_| ".".into());
+    let path = std::path::Path::new(&root).join("src").join(&value);
+    match std::fs::read_to_string(&path) {
+        Ok(grammar) => Ok((grammar, quote! {
+            include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/src/", #value))
+        })),
+        Err(e) => Err(syn::Error::new_spanned(
+            attr,
+            format!("cannot read grammar {}: {}", path.display(), e),
+        )),
+    }
+}
 
-                if interned.is_none() {
-                    set.insert(s.into());
-                    interned = set.get(s);
-                }
-                unsafe { mem::transmute(interned.unwrap().as_str()) }
-            }
-        }
+/// Checks that the grammar is valid and follows the conventions of uindex,
+/// and that the unique prefix is within the var range, the only one in which
+/// values can be replaced.
+pub fn check_grammar(
+    attr: &syn::Attribute,
+    grammar: &str,
+    var_names: &VarNames,
+) -> Result<(), TokenStream> {
+    let (unique, range) = (var_names.unique_prefix, var_names.var_range_prefix);
+    if !unique.starts_with(range) {
+        let message = format!(
+            "the unique prefix `{}` does not start with the var range prefix `{}`",
+            unique, range
+        );
+        return Err(syn::Error::new_spanned(attr, message).to_compile_error());
+    }
+    let messages: Vec<String> = match Schema::from_grammar_with_var_names(grammar, var_names) {
+        Err(errors) => errors.iter().map(|e| format!("invalid grammar:\n{}", e)).collect(),
+        Ok(schema) => match schema.check() {
+            Ok(()) => return Ok(()),
+            Err(errors) => errors
+                .iter()
+                .map(|e| match e {
+                    SchemaError::MissingVar => format!("{}, `{}`", e, var_names.var_rule),
+                    _ => e.to_string(),
+                })
+                .collect(),
+        },
+    };
+    let errors = messages
+        .iter()
+        .map(|message| syn::Error::new_spanned(attr, message).to_compile_error());
+    Err(quote! { #(#errors)* })
+}
+
+pub fn derive_parser(
+    attr: &syn::Attribute,
+    grammar: TokenStream,
+    parse_text: TokenStream,
+    var_names: TokenStream,
+) -> TokenStream {
+    quote! {
 
         pub struct MPParser {
             pub lexicon: Box<Lexicon>,
@@ -54,90 +141,40 @@ pub fn derive_parser(attr: &syn::Attribute) -> TokenStream {
         #attr
         pub struct FactParser;
 
-        impl<'a> MPParser {
+        impl RuleName for Rule {
+            fn rule_name(&self) -> String {
+                format!("{:?}", self)
+            }
+        }
+
+        impl MPParser {
 
             pub fn new() -> MPParser {
+                MPParser::with_hasher(MPBuildHasher::default())
+            }
+            pub fn with_hasher(hasher: MPBuildHasher) -> MPParser {
                 MPParser {
-                    lexicon: Box::new(Lexicon::new()),
+                    lexicon: Box::new(Lexicon::with_hasher(hasher)),
                 }
             }
-            fn calculate_name_hash(&self, name: &str) -> u64 {
-                let mut s = DefaultHasher::new();
-                name.hash(&mut s);
-                s.finish()
-            }
-            fn calculate_hash(&self, name: &str, text: &str, is_leaf: bool) -> u64 {
-                let mut s = DefaultHasher::new();
-                name.hash(&mut s);
-                text.hash(&mut s);
-                is_leaf.hash(&mut s);
-                s.finish()
-            }
+        }
 
-            pub fn parse_text(&'a self, text: &'a str) -> Result<ParseResult<'a>, Error<kparser::Rule>> {
-                let parse_tree = kparser::KParser::parse(kparser::Rule::knowledge, text)?.next().expect("initial parse tree");
-                let mut facts: Vec<&'a str> = vec![];
-                for pair in parse_tree.into_inner() {
-                    match pair.as_rule() {
-                        kparser::Rule::fact => {
-                            facts.push(pair.as_str());
-                        },
-                        _ => {}
-                    }
-                }
-                Ok(ParseResult { facts })
+        impl<'a> SentenceParser<'a> for MPParser {
+            type Rule = Rule;
+
+            fn lexicon(&self) -> &Lexicon {
+                &self.lexicon
             }
 
-            pub fn parse_fact(&'a self, text: &'a str) -> Vec<MPPath<'a>> {
-                let parse_tree = FactParser::parse(Rule::fact, text).ok().expect("fact pairset").next().expect("fact pair");
-                self.visit_parse_node(parse_tree,
-                                      vec![],
-                                      vec![])
+            fn grammar(&self) -> &str {
+                #grammar
             }
 
-            fn visit_parse_node(&'a self,
-                                parse_tree: Pair<'a, Rule>,
-                                mut root_segments: Vec<TSegment>,
-                                mut all_paths: Vec<MPPath<'a>>,
-                            ) -> Vec<MPPath> {
-                let text = parse_tree.as_str();
-                let is_empty = text.trim().is_empty();
-                if is_empty {
-                    return all_paths;
-                }
-                let rule = parse_tree.as_rule();
-                let name = format!("{:?}", rule);
-                let is_var = name == constants::VAR_RULE_NAME;
-                let in_var_range = name.starts_with(constants::VAR_RANGE_PREFIX);
-                let unique = name.starts_with(constants::UNIQUE_PREFIX);
-                let mut children = parse_tree.into_inner().peekable();
-                let is_leaf = children.peek().is_none();
-                let mut new_root_segments: Option<Vec<TSegment>> = None;
-                if !is_leaf {
-                    let mut pre_new_root_segments = root_segments.clone();
-                    let text_hash = self.calculate_name_hash(text);
-                    let name_hash = self.calculate_name_hash(name.as_str());
-                    let tsegment = TSegment { name: name_hash, text: text_hash };
-                    pre_new_root_segments.push(tsegment);
-                    new_root_segments = Some(pre_new_root_segments);
-                }
-                if in_var_range || (is_leaf && !is_empty) {
-                    let key = self.calculate_hash(name.as_str(), text, is_leaf);
-                    info!("Interning segment '{}' with text: {}", name, text);
-                    let segment = self.lexicon.intern_with_name(self.calculate_name_hash(name.as_str()), text, key, is_leaf, is_var, in_var_range, is_empty, unique);
-                    let new_path = MPPath::new(root_segments, segment);
-                    all_paths.push(new_path);
-                }
-                if !is_leaf {
-                    let next_root_segments = new_root_segments.unwrap();
-                    for child in children {
-                        all_paths = self.visit_parse_node(child,
-                                                          next_root_segments.clone(),
-                                                          all_paths);
-                    }
-                }
-                all_paths
+            fn parse_tree(&'a self, text: &'a str) -> Result<Pair<'a, Rule>, Error<Rule>> {
+                Ok(FactParser::parse(Rule::fact, text)?.next().expect("fact pair"))
             }
+            #parse_text
+            #var_names
         }
     }
 }
@@ -17,7 +17,6 @@
 // along with any part of the modus_ponens project.
 // If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::hash_map::DefaultHasher;
 use std::fmt;
 use std::hash::{Hash, Hasher};
 
@@ -51,22 +50,27 @@ impl Hash for TSegment {
     }
 }
 
+/// Identifies a path by the lexicon key of the names of its segments
+/// and by the id of its value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PathKey {
+    pub chain: u64,
+    pub value: u64,
+}
+
 #[derive(Debug, Clone)]
 pub struct MPPath<'a> {
     pub value: &'a MPSegment,
     pub segments: Vec<TSegment>,
-    pub identity: u64,
+    pub identity: PathKey,
 }
 
 impl<'a> MPPath<'a> {
-    pub fn new(segments: Vec<TSegment>, value: &'a MPSegment) -> MPPath {
-        let mut hasher = DefaultHasher::new();
-        for segment in segments.iter() {
-            segment.name.hash(&mut hasher);
-        }
-        value.name.hash(&mut hasher);
-        value.text.hash(&mut hasher);
-        let identity = hasher.finish();
+    pub fn new(segments: Vec<TSegment>, chain: u64, value: &'a MPSegment) -> MPPath<'a> {
+        let identity = PathKey {
+            chain,
+            value: value.id,
+        };
         MPPath {
             value,
             segments,
@@ -76,52 +80,52 @@ impl<'a> MPPath<'a> {
     pub fn len(&self) -> usize {
         self.segments.len()
     }
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
     pub fn starts_with(&self, path: &MPPath) -> bool {
         let lpath = path.len();
-        self.len() >= lpath && &self.segments[0..lpath] == &path.segments[0..lpath]
+        self.len() >= lpath && self.segments[0..lpath] == path.segments[0..lpath]
     }
-    pub fn paths_after(&'a self, paths: &'a [MPPath]) -> usize {
-        let mut seen = false;
-        let mut path_starts_with_self: bool;
-        let mut i = 0;
-        for path in paths {
-            if path.value.is_empty {
-                i += 1;
-                continue;
-            }
-            path_starts_with_self = path.starts_with(&self);
-            if path_starts_with_self {
-                seen = true;
-            } else if seen {
-                break;
-            }
-            i += 1;
-        }
-        i as usize
+    /// Whether the path is within the (non terminal) value of this path.
+    pub fn contains(&self, path: &MPPath) -> bool {
+        let len = self.len();
+        path.len() > len
+            && path.segments[..len] == self.segments[..]
+            && path.segments[len].name == self.value.name
+            && path.segments[len].text == self.value.id
+    }
+    /// How many of the given paths, which follow this one in a sentence,
+    /// are within its value, so that the rest of the sentence starts after them.
+    pub fn paths_within(&self, paths: &[MPPath]) -> usize {
+        paths
+            .iter()
+            .take_while(|path| path.value.is_empty || self.contains(path))
+            .count()
     }
 
-    pub fn substitute(&'a self, matching: &'a MPMatching) -> MPPath {
+    pub fn substitute(&'a self, matching: &'a MPMatching) -> MPPath<'a> {
         let new_segments = self.segments.clone();
-        let new_value = get_or_key(&matching, &self.value);
-        MPPath::new(new_segments, new_value)
+        let new_value = get_or_key(matching, self.value);
+        MPPath::new(new_segments, self.identity.chain, new_value)
     }
 }
 
-impl<'a> fmt::Display for MPPath<'_> {
+impl fmt::Display for MPPath<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "<{}>", self.value)
     }
 }
 
-impl<'a> PartialEq for MPPath<'_> {
+impl PartialEq for MPPath<'_> {
     fn eq(&self, other: &Self) -> bool {
         self.identity == other.identity
     }
 }
 
-impl<'a> Eq for MPPath<'_> {}
+impl Eq for MPPath<'_> {}
 
-impl<'a> Hash for MPPath<'_> {
+impl Hash for MPPath<'_> {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.identity.hash(state);
     }
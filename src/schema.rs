@@ -0,0 +1,202 @@
+// Copyright (c) 2020 by Enrique Pérez Arnaud <enrique at cazalla.net>
+//
+// This file is part of the modus_ponens project.
+// http://www.modus_ponens.net
+//
+// The modus_ponens project is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// The modus_ponens project is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with any part of the modus_ponens project.
+// If not, see <http://www.gnu.org/licenses/>.
+
+//! Introspection of the grammar of a db.
+
+use std::fmt;
+
+use pest::error::Error;
+use pest_meta::ast::{Expr, Rule as AstRule, RuleType};
+use pest_meta::parser::{self, Rule as GrammarRule};
+use pest_meta::validator;
+
+use crate::sentence::VarNames;
+
+/// Parses and validates a pest grammar.
+pub fn parse_grammar(grammar: &str) -> Result<Vec<AstRule>, Vec<Error<GrammarRule>>> {
+    let pairs = parser::parse(GrammarRule::grammar_rules, grammar).map_err(|e| vec![e])?;
+    validator::validate_pairs(pairs.clone())?;
+    parser::consume_rules(pairs)
+}
+
+/// A production in the grammar of a db.
+#[derive(Debug, Clone)]
+pub struct Production {
+    pub name: String,
+    pub ty: RuleType,
+    pub expr: Expr,
+    pub is_var: bool,
+    pub in_var_range: bool,
+    pub unique: bool,
+}
+
+impl Production {
+    fn new(rule: AstRule, var_names: &VarNames) -> Production {
+        let AstRule { name, ty, expr } = rule;
+        Production {
+            is_var: var_names.is_var(&name),
+            in_var_range: var_names.in_var_range(&name),
+            unique: var_names.is_unique(&name),
+            name,
+            ty,
+            expr,
+        }
+    }
+    /// Names of the productions this production refers to,
+    /// in the order in which they first appear in its expression.
+    pub fn references(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = vec![];
+        collect_references(&self.expr, &mut names);
+        names
+    }
+}
+
+fn collect_alternatives<'e>(expr: &'e Expr, alternatives: &mut Vec<&'e Expr>) {
+    match expr {
+        Expr::Choice(lhs, rhs) => {
+            collect_alternatives(lhs, alternatives);
+            collect_alternatives(rhs, alternatives);
+        }
+        _ => alternatives.push(expr),
+    }
+}
+
+fn collect_references<'e>(expr: &'e Expr, names: &mut Vec<&'e str>) {
+    match expr {
+        Expr::Ident(name) if !names.contains(&name.as_str()) => names.push(name.as_str()),
+        Expr::Seq(lhs, rhs) | Expr::Choice(lhs, rhs) => {
+            collect_references(lhs, names);
+            collect_references(rhs, names);
+        }
+        Expr::PosPred(expr)
+        | Expr::NegPred(expr)
+        | Expr::Opt(expr)
+        | Expr::Rep(expr)
+        | Expr::RepOnce(expr)
+        | Expr::RepExact(expr, _)
+        | Expr::RepMin(expr, _)
+        | Expr::RepMax(expr, _)
+        | Expr::RepMinMax(expr, _, _)
+        | Expr::Push(expr) => collect_references(expr, names),
+        _ => {}
+    }
+}
+
+/// The productions in the grammar of a db, in the order in which they are defined.
+#[derive(Debug, Clone)]
+pub struct Schema {
+    pub productions: Vec<Production>,
+}
+
+impl Schema {
+    pub fn from_grammar(grammar: &str) -> Result<Schema, Vec<Error<GrammarRule>>> {
+        Schema::from_grammar_with_var_names(grammar, &VarNames::default())
+    }
+    pub fn from_grammar_with_var_names(
+        grammar: &str,
+        var_names: &VarNames,
+    ) -> Result<Schema, Vec<Error<GrammarRule>>> {
+        let rules = parse_grammar(grammar)?;
+        Ok(Schema {
+            productions: rules
+                .into_iter()
+                .map(|rule| Production::new(rule, var_names))
+                .collect(),
+        })
+    }
+    pub fn production(&self, name: &str) -> Option<&Production> {
+        self.productions.iter().find(|p| p.name == name)
+    }
+    /// The top production, whose sentences are held by the db.
+    pub fn fact(&self) -> Option<&Production> {
+        self.production("fact")
+    }
+    /// The production for variables.
+    pub fn var_rule(&self) -> Option<&Production> {
+        self.productions.iter().find(|p| p.is_var)
+    }
+    /// The productions that can be matched by variables.
+    pub fn var_range(&self) -> impl Iterator<Item = &Production> {
+        self.productions.iter().filter(|p| p.in_var_range)
+    }
+    /// The productions that can be matched by variables and that,
+    /// for a given path in the db, can only have one value.
+    pub fn unique(&self) -> impl Iterator<Item = &Production> {
+        self.productions.iter().filter(|p| p.unique)
+    }
+    /// Checks that the grammar follows the conventions uindex relies on:
+    /// there is a `fact` production, a production for variables, and,
+    /// for each production that can be matched by variables, another
+    /// production that chooses between it and a variable.
+    pub fn check(&self) -> Result<(), Vec<SchemaError>> {
+        let mut errors = vec![];
+        if self.fact().is_none() {
+            errors.push(SchemaError::MissingFact);
+        }
+        match self.var_rule() {
+            None => errors.push(SchemaError::MissingVar),
+            Some(var) => {
+                for production in self.var_range() {
+                    if !self.has_var_alternative(&var.name, &production.name) {
+                        errors.push(SchemaError::MissingVarAlternative(production.name.clone()));
+                    }
+                }
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+    fn has_var_alternative(&self, var: &str, name: &str) -> bool {
+        self.productions.iter().any(|p| {
+            let mut alternatives = vec![];
+            collect_alternatives(&p.expr, &mut alternatives);
+            let chooses = |n: &str| {
+                alternatives
+                    .iter()
+                    .any(|e| matches!(e, Expr::Ident(ident) if ident == n))
+            };
+            chooses(var) && chooses(name)
+        })
+    }
+}
+
+/// A way in which a grammar does not follow the conventions of uindex.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaError {
+    MissingFact,
+    MissingVar,
+    MissingVarAlternative(String),
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SchemaError::MissingFact => write!(f, "the grammar has no `fact` production"),
+            SchemaError::MissingVar => write!(f, "the grammar has no production for variables"),
+            SchemaError::MissingVarAlternative(name) => write!(
+                f,
+                "no production in the grammar chooses between `{}` and a variable",
+                name
+            ),
+        }
+    }
+}
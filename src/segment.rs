@@ -22,6 +22,7 @@ use std::hash::{Hash, Hasher};
 
 #[derive(Debug, Clone)]
 pub struct MPSegment {
+    pub id: u64,
     pub text: String,
     pub name: u64,
     pub is_leaf: bool,
@@ -32,7 +33,9 @@ pub struct MPSegment {
 }
 
 impl MPSegment {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
+        id: u64,
         name: u64,
         text: String,
         is_leaf: bool,
@@ -42,6 +45,7 @@ impl MPSegment {
         unique: bool,
     ) -> MPSegment {
         MPSegment {
+            id,
             name,
             text,
             is_leaf,
@@ -0,0 +1,330 @@
+// Copyright (c) 2020 by Enrique Pérez Arnaud <enrique at cazalla.net>
+//
+// This file is part of the modus_ponens project.
+// http://www.modus_ponens.net
+//
+// The modus_ponens project is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// The modus_ponens project is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with any part of the modus_ponens project.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use std::hash::Hasher;
+
+use log::info;
+use pest::error::Error;
+use pest::iterators::Pair;
+use pest::RuleType;
+
+use crate::constants;
+use crate::hashing::FnvHasher;
+use crate::kbase::FactId;
+use crate::kparser;
+use crate::lexicon::Lexicon;
+use crate::parse_result::ParseResult;
+use crate::path::{MPPath, TSegment};
+
+/// The name of a production, as given in the grammar.
+pub trait RuleName {
+    fn rule_name(&self) -> String;
+}
+
+impl RuleName for &str {
+    fn rule_name(&self) -> String {
+        self.to_string()
+    }
+}
+
+/// The names that mark productions in a grammar as the variables,
+/// as those that can be matched by variables, and as those that are unique.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VarNames {
+    pub var_rule: &'static str,
+    pub var_range_prefix: &'static str,
+    pub unique_prefix: &'static str,
+}
+
+impl VarNames {
+    pub fn is_var(&self, name: &str) -> bool {
+        name == self.var_rule
+    }
+    pub fn in_var_range(&self, name: &str) -> bool {
+        name.starts_with(self.var_range_prefix)
+    }
+    pub fn is_unique(&self, name: &str) -> bool {
+        name.starts_with(self.unique_prefix)
+    }
+}
+
+impl Default for VarNames {
+    fn default() -> Self {
+        VarNames {
+            var_rule: constants::VAR_RULE_NAME,
+            var_range_prefix: constants::VAR_RANGE_PREFIX,
+            unique_prefix: constants::UNIQUE_PREFIX,
+        }
+    }
+}
+
+/// Turns text into the paths that are stored in, or queried from, a db.
+/// Implementors only need to provide the parse tree for a single sentence.
+pub trait SentenceParser<'a> {
+    type Rule: RuleType + RuleName;
+
+    fn lexicon(&self) -> &Lexicon;
+
+    /// The source of the pest grammar for the sentences.
+    fn grammar(&self) -> &str;
+
+    fn var_names(&self) -> VarNames {
+        VarNames::default()
+    }
+
+    /// The terminator written after each sentence when knowledge is written out.
+    fn terminator(&self) -> &'static str {
+        "◊"
+    }
+
+    /// All the terminators that can end a sentence in knowledge.
+    fn terminators(&self) -> &'static [&'static str] {
+        &kparser::DEFAULT_TERMINATORS
+    }
+
+    fn parse_tree(&'a self, text: &'a str) -> Result<Pair<'a, Self::Rule>, Error<Self::Rule>>;
+
+    fn parse_text(&'a self, text: &'a str) -> Result<ParseResult<'a>, Error<kparser::Rule>> {
+        kparser::parse_knowledge::<kparser::KParser, _>(kparser::Rule::knowledge, text)
+    }
+
+    fn parse_fact(&'a self, text: &'a str) -> Vec<MPPath<'a>> {
+        let (_, _, paths) = self.parse_fact_with_template(text);
+        paths
+    }
+
+    /// Like `parse_fact`, along with the id of the sentence and the lexicon id of
+    /// its template, which with the values of its leaf paths gives back its
+    /// canonical text. Neither depends on the formatting of the sentence.
+    fn parse_fact_with_template(&'a self, text: &'a str) -> (FactId, u64, Vec<MPPath<'a>>) {
+        let parse_tree = self.parse_tree(text).expect("fact pair");
+        let lexicon: &'a Lexicon = self.lexicon();
+        let production = lexicon.intern_rule_name(&parse_tree.as_rule().rule_name());
+        let mut nodes = vec![];
+        let Canonical { gaps, hash, .. } =
+            canonical_nodes(lexicon, parse_tree.clone(), &mut nodes).unwrap_or_default();
+        let template = lexicon.intern_template(production, gaps);
+        let mut visitor = PathVisitor {
+            lexicon,
+            var_names: self.var_names(),
+            nodes: &nodes,
+            index: 0,
+            paths: vec![],
+        };
+        visitor.visit(parse_tree, vec![], 0);
+        (FactId(hash), template, visitor.paths)
+    }
+}
+
+/// A node in a parse tree, in a form that does not depend on its formatting.
+#[derive(Default)]
+struct Canonical {
+    key: u64,
+    text: String,
+    /// The text, split at the leaves of the node.
+    gaps: Vec<String>,
+    /// A hash of what the key is built from, which unlike the key,
+    /// does not depend on what was interned before in the lexicon.
+    hash: u64,
+}
+
+/// Keys and canonical texts for the non terminal nodes in a parse tree,
+/// in the order in which `PathVisitor` visits them. The key of a node
+/// is built from the keys of its children and the literal text between them,
+/// ignoring whitespace, and in its text each run of whitespace between children
+/// is collapsed into a single space, so that neither depends on formatting.
+fn canonical_nodes<'a, R: RuleType + RuleName>(
+    lexicon: &'a Lexicon,
+    parse_tree: Pair<'a, R>,
+    nodes: &mut Vec<(u64, String)>,
+) -> Option<Canonical> {
+    let text = parse_tree.as_str();
+    if text.trim().is_empty() {
+        return None;
+    }
+    let name = parse_tree.as_rule().rule_name();
+    let name_id = lexicon.intern_rule_name(name.as_str());
+    let mut hasher = FnvHasher::default();
+    hasher.write(name.as_bytes());
+    hasher.write_u8(0xff);
+    let start = parse_tree.as_span().start();
+    let mut children = parse_tree.into_inner().peekable();
+    if children.peek().is_none() {
+        hasher.write(text.as_bytes());
+        hasher.write_u8(1);
+        return Some(Canonical {
+            key: lexicon.intern_key(&[name_id, lexicon.intern_name(text), 1]),
+            text: text.to_string(),
+            gaps: vec![String::new(), String::new()],
+            hash: hasher.finish(),
+        });
+    }
+    let index = nodes.len();
+    nodes.push((0, String::new()));
+    let mut parts = vec![name_id];
+    let mut canonical = String::new();
+    let mut gaps = vec![String::new()];
+    let mut offset = 0;
+    for child in children {
+        // whitespace pairs, from a non silent WHITESPACE rule, belong to the gap
+        if child.as_str().trim().is_empty() {
+            continue;
+        }
+        let span = child.as_span();
+        let gap = &text[offset..span.start() - start];
+        canonical_gap(lexicon, gap, &mut parts, &mut canonical, &mut gaps, &mut hasher);
+        offset = span.end() - start;
+        if let Some(node) = canonical_nodes(lexicon, child, nodes) {
+            parts.push(node.key);
+            canonical.push_str(&node.text);
+            let mut child_gaps = node.gaps.into_iter();
+            if let (Some(gap), Some(first)) = (gaps.last_mut(), child_gaps.next()) {
+                gap.push_str(&first);
+            }
+            gaps.extend(child_gaps);
+            hasher.write_u64(node.hash);
+        }
+    }
+    let gap = &text[offset..];
+    canonical_gap(lexicon, gap, &mut parts, &mut canonical, &mut gaps, &mut hasher);
+    parts.push(0);
+    hasher.write_u8(0);
+    if let Some(first) = gaps.first_mut() {
+        *first = first.trim_start().to_string();
+    }
+    if let Some(last) = gaps.last_mut() {
+        *last = last.trim_end().to_string();
+    }
+    let key = lexicon.intern_key(&parts);
+    let text = canonical.trim().to_string();
+    nodes[index] = (key, text.clone());
+    Some(Canonical {
+        key,
+        text,
+        gaps,
+        hash: hasher.finish(),
+    })
+}
+
+fn canonical_gap(
+    lexicon: &Lexicon,
+    gap: &str,
+    parts: &mut Vec<u64>,
+    canonical: &mut String,
+    gaps: &mut [String],
+    hasher: &mut FnvHasher,
+) {
+    let literal: String = gap.split_whitespace().collect();
+    if !literal.is_empty() {
+        parts.push(lexicon.intern_name(literal.as_str()));
+        hasher.write(literal.as_bytes());
+        hasher.write_u8(0xff);
+    }
+    let start = canonical.len();
+    let mut in_space = false;
+    for c in gap.chars() {
+        if !c.is_whitespace() {
+            canonical.push(c);
+            in_space = false;
+        } else if !in_space {
+            canonical.push(' ');
+            in_space = true;
+        }
+    }
+    if let Some(last) = gaps.last_mut() {
+        last.push_str(&canonical[start..]);
+    }
+}
+
+/// Collects the paths for a parse tree, given the keys and canonical texts
+/// of its non terminal nodes, as obtained from `canonical_nodes`.
+struct PathVisitor<'a, 'n> {
+    lexicon: &'a Lexicon,
+    var_names: VarNames,
+    nodes: &'n [(u64, String)],
+    /// The index in `nodes` of the next non terminal node to visit.
+    index: usize,
+    paths: Vec<MPPath<'a>>,
+}
+
+impl<'a, 'n> PathVisitor<'a, 'n> {
+    fn visit<R: RuleType + RuleName>(
+        &mut self,
+        parse_tree: Pair<'a, R>,
+        root_segments: Vec<TSegment>,
+        chain: u64,
+    ) {
+        let lexicon = self.lexicon;
+        let text = parse_tree.as_str();
+        let is_empty = text.trim().is_empty();
+        if is_empty {
+            return;
+        }
+        let rule = parse_tree.as_rule();
+        let name = rule.rule_name();
+        let is_var = self.var_names.is_var(&name);
+        let in_var_range = self.var_names.in_var_range(&name);
+        let unique = self.var_names.is_unique(&name);
+        let mut children = parse_tree.into_inner().peekable();
+        let is_leaf = children.peek().is_none();
+        let name_id = lexicon.intern_rule_name(name.as_str());
+        let (key, text) = if is_leaf {
+            let text_id = lexicon.intern_name(text);
+            (lexicon.intern_key(&[name_id, text_id, 1]), text)
+        } else {
+            let (key, canonical) = &self.nodes[self.index];
+            self.index += 1;
+            (*key, canonical.as_str())
+        };
+        let mut new_root_segments: Option<Vec<TSegment>> = None;
+        if !is_leaf {
+            let mut pre_new_root_segments = root_segments.clone();
+            let tsegment = TSegment {
+                name: name_id,
+                text: key,
+            };
+            pre_new_root_segments.push(tsegment);
+            new_root_segments = Some(pre_new_root_segments);
+        }
+        if in_var_range || (is_leaf && !is_empty) {
+            info!("Interning segment '{}' with text: {}", name, text);
+            let segment = lexicon.intern_with_name(
+                name_id,
+                text,
+                key,
+                is_leaf,
+                is_var,
+                in_var_range,
+                is_empty,
+                unique,
+            );
+            self.paths.push(MPPath::new(root_segments, chain, segment));
+        }
+        if let Some(next_root_segments) = new_root_segments {
+            // the position of each child is part of its chain, so that
+            // sentences with the same leaves in different trees do not
+            // end at the same node in the fact set
+            let children = children.filter(|child| !child.as_str().trim().is_empty());
+            for (position, child) in children.enumerate() {
+                let new_chain = lexicon.intern_key(&[chain, name_id, position as u64]);
+                self.visit(child, next_root_segments.clone(), new_chain);
+            }
+        }
+    }
+}
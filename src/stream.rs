@@ -0,0 +1,163 @@
+// Copyright (c) 2020 by Enrique Pérez Arnaud <enrique at cazalla.net>
+//
+// This file is part of the modus_ponens project.
+// http://www.modus_ponens.net
+//
+// The modus_ponens project is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// The modus_ponens project is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with any part of the modus_ponens project.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use std::error;
+use std::fmt;
+use std::io;
+
+use crate::kbase::TellStatus;
+
+/// How far telling knowledge from a reader has got.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Progress {
+    /// The number of bytes of the input that have been told.
+    pub bytes: u64,
+    /// The number of sentences that have been told.
+    pub sentences: usize,
+}
+
+/// Why telling knowledge from a reader stopped. The sentences before
+/// `offset` (which is counted in bytes from the start of the input)
+/// have been told.
+#[derive(Debug)]
+pub enum TellError {
+    /// The input could not be read.
+    Io { offset: u64, error: io::Error },
+    /// The input is not valid UTF-8.
+    Utf8 { offset: u64 },
+    /// The input could not be split into sentences. The positions in the
+    /// message are relative to the part of the input after `offset`.
+    Knowledge { offset: u64, message: String },
+    /// A sentence could not be parsed. The positions in the message are
+    /// relative to the sentence, which starts at `offset`.
+    Sentence { offset: u64, message: String },
+}
+
+impl TellError {
+    /// The offset in the input at which the error was found.
+    pub fn offset(&self) -> u64 {
+        match self {
+            TellError::Io { offset, .. }
+            | TellError::Utf8 { offset }
+            | TellError::Knowledge { offset, .. }
+            | TellError::Sentence { offset, .. } => *offset,
+        }
+    }
+}
+
+impl fmt::Display for TellError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TellError::Io { offset, error } => {
+                write!(f, "error reading at byte {}: {}", offset, error)
+            }
+            TellError::Utf8 { offset } => write!(f, "invalid UTF-8 at byte {}", offset),
+            TellError::Knowledge { offset, message } => {
+                write!(
+                    f,
+                    "unparsable knowledge after byte {}:\n{}",
+                    offset, message
+                )
+            }
+            TellError::Sentence { offset, message } => {
+                write!(f, "unparsable sentence at byte {}:\n{}", offset, message)
+            }
+        }
+    }
+}
+
+impl error::Error for TellError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            TellError::Io { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// What importing knowledge did: how many sentences were added,
+/// were already present, or replaced others, and which were skipped.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ImportReport {
+    pub added: usize,
+    pub present: usize,
+    pub replaced: usize,
+    pub skipped: Vec<Skipped>,
+}
+
+impl ImportReport {
+    pub(crate) fn record(&mut self, status: TellStatus) {
+        match status {
+            TellStatus::Added => self.added += 1,
+            TellStatus::Present => self.present += 1,
+            TellStatus::Replaced => self.replaced += 1,
+        }
+    }
+}
+
+/// A sentence that could not be parsed, and was skipped in an import.
+/// Lines and columns start at 1, and columns are counted in characters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Skipped {
+    pub line: usize,
+    pub column: usize,
+    pub offset: u64,
+    pub sentence: String,
+    /// The pest error, with positions relative to the sentence.
+    pub error: String,
+}
+
+impl fmt::Display for Skipped {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "skipped sentence at line {}, column {}:\n{}",
+            self.line, self.column, self.error
+        )
+    }
+}
+
+/// A line and column in some input.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct LineColumn {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl LineColumn {
+    pub(crate) fn start() -> LineColumn {
+        LineColumn { line: 1, column: 1 }
+    }
+    /// Where the input is after the text, if the text starts here.
+    pub(crate) fn after(self, text: &str) -> LineColumn {
+        match text.rfind('\n') {
+            Some(last) => LineColumn {
+                line: self.line + text.matches('\n').count(),
+                column: text[last + 1..].chars().count() + 1,
+            },
+            None => LineColumn {
+                line: self.line,
+                column: self.column + text.chars().count(),
+            },
+        }
+    }
+}
@@ -0,0 +1,114 @@
+// Copyright (c) 2020 by Enrique Pérez Arnaud <enrique at cazalla.net>
+//
+// This file is part of the modus_ponens project.
+// http://www.modus_ponens.net
+//
+// The modus_ponens project is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// The modus_ponens project is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with any part of the modus_ponens project.
+// If not, see <http://www.gnu.org/licenses/>.
+
+//! Rust types that correspond to sentences in the grammar of a db.
+
+use crate::bindings::Tree;
+
+/// A type whose values can be told to, and asked from, a db as sentences.
+/// It is usually derived with `#[derive(Sentence)]`, mapping the type (or each
+/// variant of an enum) to a production of the grammar, as in `#[production = "user"]`,
+/// and giving a template for its sentences, as in
+/// `#[sentence = "U {given_name} {surname} {userid}"]`. Each field is formatted
+/// with `Display` into the template, and parsed with `FromStr` from the text of
+/// its child of the production in the parse tree of a sentence.
+pub trait Sentence: Sized {
+    /// The text of the sentence for the value, without terminator.
+    fn to_sentence(&self) -> String;
+
+    /// The value for the parse tree of a sentence, if it has a node for
+    /// the production of the type, with a child for each of its fields.
+    fn from_tree(tree: &Tree) -> Option<Self>;
+}
+
+/// A part of a template, either some literal text or a placeholder for a field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplatePart {
+    Literal(String),
+    Field(String),
+}
+
+/// Splits a template into literal text and placeholders, which are
+/// field names or indexes within braces. Braces are escaped as in `format!`.
+pub fn parse_template(template: &str) -> Result<Vec<TemplatePart>, String> {
+    let mut parts = vec![];
+    let mut literal = String::new();
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                literal.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                literal.push('}');
+            }
+            '{' => {
+                let mut field = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) if c.is_alphanumeric() || c == '_' => field.push(c),
+                        _ => return Err(format!("bad placeholder in template `{}`", template)),
+                    }
+                }
+                if field.is_empty() {
+                    return Err(format!("empty placeholder in template `{}`", template));
+                }
+                if let Some(TemplatePart::Field(_)) = parts.last() {
+                    if literal.is_empty() {
+                        return Err(format!(
+                            "placeholders must be separated in template `{}`",
+                            template
+                        ));
+                    }
+                }
+                if !literal.is_empty() {
+                    parts.push(TemplatePart::Literal(literal));
+                    literal = String::new();
+                }
+                parts.push(TemplatePart::Field(field));
+            }
+            '}' => return Err(format!("unmatched `}}` in template `{}`", template)),
+            c => literal.push(c),
+        }
+    }
+    if !literal.is_empty() {
+        parts.push(TemplatePart::Literal(literal));
+    }
+    Ok(parts)
+}
+
+/// The texts of the children of the first node for the production in the
+/// parse tree, in order, which are the values of the fields of a type mapped
+/// to the production, in the order in which they appear in its template.
+pub fn field_values<'t>(production: &str, tree: &'t Tree) -> Option<Vec<&'t str>> {
+    if tree.production == production {
+        return Some(
+            tree.children
+                .iter()
+                .map(|child| child.text.as_str())
+                .collect(),
+        );
+    }
+    tree.children
+        .iter()
+        .find_map(|child| field_values(production, child))
+}
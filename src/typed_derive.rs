@@ -0,0 +1,258 @@
+// Copyright (c) 2020 by Enrique Pérez Arnaud <enrique at cazalla.net>
+//
+// This file is part of the modus_ponens project.
+// http://www.modus_ponens.net
+//
+// The modus_ponens project is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// The modus_ponens project is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with any part of the modus_ponens project.
+// If not, see <http://www.gnu.org/licenses/>.
+
+extern crate proc_macro2;
+extern crate syn;
+
+use proc_macro2::TokenStream;
+
+use crate::knowledge::string_value;
+use crate::typed::{parse_template, TemplatePart};
+
+/// Implements `Sentence` for a struct, or an enum, from the productions and
+/// the templates given in `#[production = "..."]` and `#[sentence = "..."]`
+/// attributes, on the struct or on each variant.
+pub fn derive_sentence(input: TokenStream) -> TokenStream {
+    let ast: syn::DeriveInput = match syn::parse2(input) {
+        Ok(ast) => ast,
+        Err(e) => return e.to_compile_error(),
+    };
+    match sentence_impl(&ast) {
+        Ok(derived) => derived,
+        Err(e) => e.to_compile_error(),
+    }
+}
+
+fn sentence_impl(ast: &syn::DeriveInput) -> syn::Result<TokenStream> {
+    let name = &ast.ident;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    let (to_sentence, from_sentence) = match &ast.data {
+        syn::Data::Struct(data) => {
+            let template = attribute(&ast.attrs, "sentence", name)?;
+            let production = attribute(&ast.attrs, "production", name)?;
+            let form = Form::new(&template, &production, &data.fields, name)?;
+            let to_sentence = form.format(quote! { #name });
+            let from_sentence = form.parse(quote! { #name });
+            (
+                quote! {
+                    match self {
+                        #to_sentence
+                    }
+                },
+                quote! {
+                    #from_sentence
+                    None
+                },
+            )
+        }
+        syn::Data::Enum(data) => {
+            let mut to_sentence = vec![];
+            let mut from_sentence = vec![];
+            for variant in &data.variants {
+                let ident = &variant.ident;
+                let template = attribute(&variant.attrs, "sentence", ident)?;
+                let production = attribute(&variant.attrs, "production", ident)?;
+                let form = Form::new(&template, &production, &variant.fields, ident)?;
+                to_sentence.push(form.format(quote! { #name::#ident }));
+                from_sentence.push(form.parse(quote! { #name::#ident }));
+            }
+            (
+                quote! {
+                    match self {
+                        #(#to_sentence)*
+                    }
+                },
+                quote! {
+                    #(#from_sentence)*
+                    None
+                },
+            )
+        }
+        syn::Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                name,
+                "sentences can only be derived for structs and enums",
+            ))
+        }
+    };
+    Ok(quote! {
+        impl #impl_generics ::uindex::typed::Sentence for #name #ty_generics #where_clause {
+            fn to_sentence(&self) -> String {
+                #to_sentence
+            }
+
+            fn from_tree(tree: &::uindex::bindings::Tree) -> Option<Self> {
+                #from_sentence
+            }
+        }
+    })
+}
+
+fn attribute<T: quote::ToTokens>(
+    attrs: &[syn::Attribute],
+    name: &str,
+    spanned: T,
+) -> syn::Result<String> {
+    match attrs.iter().find(|attr| attr.path.is_ident(name)) {
+        Some(attr) => string_value(attr),
+        None => {
+            let message = format!("expected a #[{} = \"...\"] attribute", name);
+            Err(syn::Error::new_spanned(spanned, message))
+        }
+    }
+}
+
+/// The template and the production for a struct or a variant, with the bindings
+/// for its fields in the order in which they appear in the template.
+struct Form {
+    template: String,
+    production: String,
+    fields: Vec<syn::Member>,
+    bindings: Vec<syn::Ident>,
+    braced: bool,
+    unit: bool,
+}
+
+impl Form {
+    fn new(
+        template: &str,
+        production: &str,
+        fields: &syn::Fields,
+        ident: &syn::Ident,
+    ) -> syn::Result<Form> {
+        let error = |message: String| syn::Error::new_spanned(ident, message);
+        let members: Vec<syn::Member> = match fields {
+            syn::Fields::Named(named) => named
+                .named
+                .iter()
+                .map(|f| syn::Member::Named(f.ident.clone().unwrap()))
+                .collect(),
+            syn::Fields::Unnamed(unnamed) => (0..unnamed.unnamed.len())
+                .map(|i| syn::Member::Unnamed(i.into()))
+                .collect(),
+            syn::Fields::Unit => vec![],
+        };
+        let member_name = |m: &syn::Member| match m {
+            syn::Member::Named(ident) => ident.to_string(),
+            syn::Member::Unnamed(index) => index.index.to_string(),
+        };
+        let mut ordered = vec![];
+        for part in parse_template(template).map_err(error)? {
+            if let TemplatePart::Field(field) = part {
+                let member = members
+                    .iter()
+                    .find(|m| member_name(m) == field)
+                    .ok_or_else(|| error(format!("no field `{}` for the template", field)))?;
+                if ordered.contains(member) {
+                    return Err(error(format!(
+                        "field `{}` is repeated in the template",
+                        field
+                    )));
+                }
+                ordered.push(member.clone());
+            }
+        }
+        if let Some(missing) = members.iter().find(|m| !ordered.contains(m)) {
+            return Err(error(format!(
+                "field `{}` is not in the template",
+                member_name(missing)
+            )));
+        }
+        let bindings = ordered
+            .iter()
+            .map(|m| format_ident!("__{}", member_name(m)))
+            .collect();
+        Ok(Form {
+            template: template.to_string(),
+            production: production.to_string(),
+            fields: ordered,
+            bindings,
+            braced: matches!(fields, syn::Fields::Named(_)),
+            unit: matches!(fields, syn::Fields::Unit),
+        })
+    }
+
+    fn pattern(&self, path: TokenStream) -> TokenStream {
+        let Form {
+            fields, bindings, ..
+        } = self;
+        if self.unit {
+            quote! { #path }
+        } else if self.braced {
+            quote! { #path { #(#fields: #bindings),* } }
+        } else {
+            let mut sorted: Vec<(&syn::Member, &syn::Ident)> =
+                fields.iter().zip(bindings).collect();
+            sorted.sort_by_key(|(m, _)| match m {
+                syn::Member::Unnamed(index) => index.index,
+                _ => 0,
+            });
+            let bindings = sorted.iter().map(|(_, b)| b);
+            quote! { #path ( #(#bindings),* ) }
+        }
+    }
+
+    /// A match arm that formats the value with the template.
+    fn format(&self, path: TokenStream) -> TokenStream {
+        let pattern = self.pattern(path);
+        let format = template_for_format(&self.template);
+        let bindings = &self.bindings;
+        quote! {
+            #pattern => format!(#format, #(#bindings),*),
+        }
+    }
+
+    /// A statement that returns the value if the parse tree has a node for the
+    /// production, with a child for each field.
+    fn parse(&self, path: TokenStream) -> TokenStream {
+        let pattern = self.pattern(path);
+        let production = &self.production;
+        let bindings = &self.bindings;
+        let count = bindings.len();
+        let indexes = 0..count;
+        quote! {
+            let values = ::uindex::typed::field_values(#production, tree);
+            if let Some(values) = values.filter(|values| values.len() == #count) {
+                let value = (|| {
+                    #(let #bindings = values[#indexes].parse().ok()?;)*
+                    Some(#pattern)
+                })();
+                if value.is_some() {
+                    return value;
+                }
+            }
+        }
+    }
+}
+
+/// The template with its placeholders replaced by positional `{}`,
+/// so that it can be given to `format!` with the fields in order.
+fn template_for_format(template: &str) -> String {
+    let parts = parse_template(template).expect("a checked template");
+    let mut format = String::new();
+    for part in parts {
+        match part {
+            TemplatePart::Literal(literal) => {
+                format.push_str(&literal.replace('{', "{{").replace('}', "}}"))
+            }
+            TemplatePart::Field(_) => format.push_str("{}"),
+        }
+    }
+    format
+}
@@ -0,0 +1,84 @@
+// Copyright (c) 2020 by Enrique Pérez Arnaud <enrique at cazalla.net>
+//
+// This file is part of the modus_ponens project.
+// http://www.modus_ponens.net
+//
+// The modus_ponens project is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// The modus_ponens project is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with any part of the modus_ponens project.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+
+use uindex::dynamic::DynamicDB;
+use uindex::kbase::DataBase;
+
+const GRAMMAR: &str = r#"
+fact        = { person ~ "lives" ~ "in" ~ place }
+var         = @{ "X" ~ ASCII_DIGIT* }
+v_name      = @{ ASCII_ALPHA+ }
+person      = _{ var | v_name }
+v_address   = { "(" ~ street ~ number ~ ")" }
+v_street    = @{ ASCII_ALPHA+ }
+street      = _{ var | v_street }
+v_number    = @{ ASCII_DIGIT+ }
+number      = _{ var | v_number }
+place       = _{ var | v_address | v_name }
+WHITESPACE  = _{ " " | "\t" | NEWLINE }
+"#;
+
+#[test]
+fn bindings_by_variable_name() {
+    let db = DynamicDB::from_grammar(GRAMMAR).unwrap();
+    db.tell("susan lives in paris ◊");
+    let answers = db.ask_bindings("X2 lives in X1 ◊");
+    assert_eq!(answers.len(), 1);
+    let bindings = &answers[0];
+    assert_eq!(bindings.get("X1"), Some("paris"));
+    assert_eq!(bindings.get("X2"), Some("susan"));
+    assert_eq!(bindings.get("X3"), None);
+    assert_eq!(bindings.production("X1"), Some("v_name"));
+    let vars: Vec<&str> = bindings.iter().map(|b| b.var.as_str()).collect();
+    assert_eq!(vars, ["X2", "X1"]);
+    assert_eq!(bindings.len(), 2);
+    let map: HashMap<String, String> = answers[0].clone().into();
+    assert_eq!(map["X2"], "susan");
+    assert_eq!(map.len(), 2);
+}
+
+#[test]
+fn non_terminal_bindings_have_their_structure() {
+    let db = DynamicDB::from_grammar(GRAMMAR).unwrap();
+    db.tell("susan lives in (main  5) ◊");
+    let answers = db.ask_bindings("susan lives in X1 ◊");
+    let binding = answers[0].binding("X1").unwrap();
+    assert_eq!(binding.value, "(main 5)");
+    assert_eq!(binding.production, "v_address");
+    let tree = binding.tree.as_ref().unwrap();
+    assert_eq!(tree.production, "v_address");
+    let children: Vec<(&str, &str)> = tree
+        .children
+        .iter()
+        .map(|child| (child.production.as_str(), child.text.as_str()))
+        .collect();
+    assert_eq!(children, [("v_street", "main"), ("v_number", "5")]);
+}
+
+#[test]
+fn no_bindings_without_variables() {
+    let db = DynamicDB::from_grammar(GRAMMAR).unwrap();
+    db.tell("susan lives in paris ◊");
+    let answers = db.ask_bindings("susan lives in paris ◊");
+    assert_eq!(answers.len(), 1);
+    assert!(answers[0].is_empty());
+    assert!(db.ask_bindings("john lives in X1 ◊").is_empty());
+}
@@ -0,0 +1,144 @@
+// Copyright (c) 2020 by Enrique Pérez Arnaud <enrique at cazalla.net>
+//
+// This file is part of the modus_ponens project.
+// http://www.modus_ponens.net
+//
+// The modus_ponens project is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// The modus_ponens project is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with any part of the modus_ponens project.
+// If not, see <http://www.gnu.org/licenses/>.
+
+extern crate uindex;
+#[macro_use]
+extern crate uindex_derive;
+#[macro_use]
+extern crate pest_derive;
+
+use quote::quote;
+use uindex::kbase::{DBGen, DataBase, TellStatus};
+use uindex::sentence::SentenceParser;
+
+#[derive(DBGen)]
+#[grammar_inline = r#"
+fact        = { name ~ "likes" ~ name }
+variable    = @{ "?" ~ ASCII_ALPHA+ }
+r_name      = @{ ASCII_ALPHA+ }
+name        = _{ variable | r_name }
+WHITESPACE  = _{ " " | "\t" | NEWLINE }
+"#]
+#[var_rule = "variable"]
+#[var_range_prefix = "r_"]
+pub struct Likes;
+
+#[derive(DBGen)]
+#[grammar_inline = r#"
+fact        = { name ~ "is" ~ age }
+var         = @{ "X" ~ ASCII_DIGIT* }
+v_name      = @{ ASCII_ALPHA+ }
+name        = _{ var | v_name }
+v_one_age   = @{ ASCII_DIGIT+ }
+age         = _{ var | v_one_age }
+WHITESPACE  = _{ " " | "\t" | NEWLINE }
+"#]
+#[unique_prefix = "v_one_"]
+pub struct Ages;
+
+#[test]
+fn renamed_variables() {
+    let db = Likes::gen_db();
+    let var_names = db.parser().var_names();
+    assert_eq!(var_names.var_rule, "variable");
+    assert_eq!(var_names.var_range_prefix, "r_");
+    assert_eq!(var_names.unique_prefix, "r_u_");
+    db.tell("susan likes oranges ◊ john likes apples ◊");
+    assert_eq!(db.ask("?who likes oranges ◊").len(), 1);
+    assert_eq!(db.ask("?who likes ?what ◊").len(), 2);
+    assert_eq!(db.ask("who likes oranges ◊").len(), 0);
+}
+
+#[test]
+fn renamed_unique_prefix() {
+    let db = Ages::gen_db();
+    assert_eq!(db.tell("susan is 30 ◊")[0].status, TellStatus::Added);
+    assert_eq!(db.tell("susan is 31 ◊")[0].status, TellStatus::Replaced);
+    assert_eq!(db.ask("susan is 30 ◊").len(), 0);
+    assert_eq!(db.ask("susan is X1 ◊").len(), 1);
+}
+
+// both derived in the same module, along with their generated items
+#[test]
+fn several_dbs_in_one_module() {
+    let likes: LikesDB = Likes::gen_db();
+    let ages: AgesDB = Ages::gen_db();
+    likes.tell("susan likes oranges ◊");
+    ages.tell("susan is 30 ◊");
+    assert_eq!(likes.ask("?who likes oranges ◊").len(), 1);
+    assert_eq!(ages.ask("X1 is 30 ◊").len(), 1);
+}
+
+fn derive_errors(grammar: &str) -> String {
+    let input = quote! {
+        #[grammar_inline = #grammar]
+        pub struct Invalid;
+    };
+    uindex::derive_dbase(input).to_string()
+}
+
+#[test]
+fn invalid_grammars_are_compile_errors() {
+    let errors = derive_errors("sentence = { v_name }\nv_name = @{ ASCII_ALPHA+ }");
+    assert!(errors.contains("compile_error"));
+    assert!(errors.contains("the grammar has no `fact` production"));
+    assert!(errors.contains("the grammar has no production for variables, `var`"));
+    let errors = derive_errors("fact = { v_name }\nvar = @{ \"X\" }\nv_name = @{ ASCII_ALPHA+ }");
+    assert!(errors.contains("chooses between `v_name` and a variable"));
+    let errors = derive_errors("fact = { ");
+    assert!(errors.contains("invalid grammar"));
+    let errors = uindex::derive_dbase(quote! { pub struct Missing; }).to_string();
+    assert!(errors.contains("expected a #[grammar"));
+    let errors = uindex::derive_dbase(quote! {
+        #[grammar_inline = "fact = { v_name }\nvar = @{ \"X\" }\nv_name = _{ var | one_name }"]
+        #[unique_prefix = "one_"]
+        pub struct Outside;
+    })
+    .to_string();
+    assert!(
+        errors.contains("the unique prefix `one_` does not start with the var range prefix `v_`")
+    );
+}
+
+#[test]
+fn malformed_attributes_are_compile_errors() {
+    let grammar =
+        "fact = { name }\nvar = @{ \"X\" }\nv_name = @{ ASCII_ALPHA+ }\nname = _{ var | v_name }";
+    let errors = uindex::derive_dbase(quote! {
+        #[grammar_inline = #grammar]
+        #[terminator(";")]
+        pub struct Malformed;
+    })
+    .to_string();
+    assert!(errors.contains("expected a string value for the #[terminator] attribute"));
+    let errors = uindex::derive_dbase(quote! {
+        #[grammar_inline = #grammar]
+        #[var_rule = 3]
+        pub struct Malformed;
+    })
+    .to_string();
+    assert!(errors.contains("expected a string value for the #[var_rule] attribute"));
+    let errors = uindex::derive_sentence(quote! {
+        #[production = "fact"]
+        #[sentence]
+        pub struct Malformed(String);
+    })
+    .to_string();
+    assert!(errors.contains("expected a string value for the #[sentence] attribute"));
+}
@@ -0,0 +1,134 @@
+// Copyright (c) 2020 by Enrique Pérez Arnaud <enrique at cazalla.net>
+//
+// This file is part of the modus_ponens project.
+// http://www.modus_ponens.net
+//
+// The modus_ponens project is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// The modus_ponens project is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with any part of the modus_ponens project.
+// If not, see <http://www.gnu.org/licenses/>.
+
+extern crate uindex;
+#[macro_use]
+extern crate uindex_derive;
+#[macro_use]
+extern crate pest_derive;
+
+use uindex::dynamic::{DynamicDB, DynamicParser};
+use uindex::kbase::{DBGen, DataBase, TellStatus};
+use uindex::path::MPPath;
+use uindex::sentence::SentenceParser;
+
+const GRAMMAR: &str = r#"
+fact        = { person ~ "is" ~ age ~ "and" ~ "lives" ~ "in" ~ place }
+var         = @{ "X" ~ ASCII_DIGIT* }
+v_name      = @{ ASCII_ALPHA+ }
+person      = _{ var | v_name }
+v_u_age     = @{ ASCII_DIGIT+ }
+age         = _{ var | v_u_age }
+v_place     = { "(" ~ person ~ ")" }
+place       = _{ var | v_place | v_name }
+WHITESPACE  = _{ " " | "\t" | NEWLINE }
+"#;
+
+#[derive(DBGen)]
+#[grammar_inline = r#"
+fact        = { person ~ "is" ~ age ~ "and" ~ "lives" ~ "in" ~ place }
+var         = @{ "X" ~ ASCII_DIGIT* }
+v_name      = @{ ASCII_ALPHA+ }
+person      = _{ var | v_name }
+v_u_age     = @{ ASCII_DIGIT+ }
+age         = _{ var | v_u_age }
+v_place     = { "(" ~ person ~ ")" }
+place       = _{ var | v_place | v_name }
+WHITESPACE  = _{ " " | "\t" | NEWLINE }
+"#]
+pub struct Derived;
+
+fn summary(paths: Vec<MPPath>) -> Vec<(String, bool, bool, bool, bool, usize)> {
+    paths
+        .iter()
+        .map(|path| {
+            let value = path.value;
+            (
+                value.text.clone(),
+                value.is_leaf,
+                value.is_var,
+                value.in_var_range,
+                value.unique,
+                path.len(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn same_paths_as_derived_parsers() {
+    let dynamic = DynamicParser::from_grammar(GRAMMAR).unwrap();
+    let derived = Derived::gen_db();
+    for text in &[
+        "susan is 30 and lives in (  john )",
+        "X1 is X2 and lives in paris",
+    ] {
+        let paths = summary(dynamic.parse_fact(text));
+        assert!(!paths.is_empty());
+        assert_eq!(paths, summary(derived.parser().parse_fact(text)));
+    }
+}
+
+#[test]
+fn follows_the_conventions_for_variables() {
+    let parser = DynamicParser::from_grammar(GRAMMAR).unwrap();
+    let paths = summary(parser.parse_fact("X1 is 30 and lives in (susan)"));
+    let texts: Vec<&str> = paths.iter().map(|path| path.0.as_str()).collect();
+    assert_eq!(texts, ["X1", "30", "(susan)", "susan"]);
+    assert!(paths[0].2 && !paths[0].3);
+    assert!(paths[1].4 && !paths[0].4);
+    assert!(!paths[2].1 && paths[2].3);
+}
+
+#[test]
+fn tells_and_asks() {
+    let db = DynamicDB::from_grammar(GRAMMAR).unwrap();
+    db.tell("susan is 30 and lives in paris ◊ john is 40 and lives in (susan) ◊");
+    assert_eq!(db.ask("X1 is X2 and lives in paris ◊").len(), 1);
+    assert_eq!(db.ask("john is 40 and lives in (susan) ◊").len(), 1);
+    assert_eq!(db.ask("john is 40 and lives in (X1) ◊").len(), 1);
+    assert_eq!(db.ask("X1 is X2 and lives in X3 ◊").len(), 2);
+    assert_eq!(db.ask("X1 is 30 and lives in paris ◊").len(), 1);
+    let told = db.tell("susan is 31 and lives in paris ◊");
+    assert_eq!(told[0].status, TellStatus::Replaced);
+    assert_eq!(db.ask("susan is 30 and lives in paris ◊").len(), 0);
+    assert_eq!(db.ask("susan is X1 and lives in paris ◊").len(), 1);
+}
+
+#[test]
+fn invalid_grammars_are_errors() {
+    assert!(DynamicDB::from_grammar("fact = { \"a\" ~ }").is_err());
+    assert!(DynamicDB::from_grammar("fact = { other }").is_err());
+}
+
+#[test]
+fn grammars_that_do_not_follow_the_conventions_are_errors() {
+    let errors = |grammar: &str| match DynamicDB::from_grammar(grammar) {
+        Ok(_) => vec![],
+        Err(errors) => errors.iter().map(|error| error.to_string()).collect(),
+    };
+    let without_fact = errors("sentence = { var }\nvar = @{ \"X\" ~ ASCII_DIGIT* }");
+    assert_eq!(without_fact.len(), 1);
+    assert!(without_fact[0].contains("no `fact` production"));
+    let without_var = errors("fact = { v_name }\nv_name = @{ ASCII_ALPHA+ }");
+    assert!(without_var
+        .iter()
+        .any(|error| error.contains("no production for variables")));
+    assert!(errors(GRAMMAR).is_empty());
+}
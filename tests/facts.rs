@@ -0,0 +1,237 @@
+// Copyright (c) 2020 by Enrique Pérez Arnaud <enrique at cazalla.net>
+//
+// This file is part of the modus_ponens project.
+// http://www.modus_ponens.net
+//
+// The modus_ponens project is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// The modus_ponens project is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with any part of the modus_ponens project.
+// If not, see <http://www.gnu.org/licenses/>.
+
+extern crate uindex;
+
+use uindex::dynamic::DynamicDB;
+use uindex::kbase::{DataBase, FactId, TellStatus};
+
+const GRAMMAR: &str = r#"
+fact        = { person ~ "is" ~ age ~ "and" ~ "lives" ~ "in" ~ place }
+var         = @{ "X" ~ ASCII_DIGIT* }
+v_name      = @{ ASCII_ALPHA+ }
+person      = _{ var | v_name }
+v_u_age     = @{ ASCII_DIGIT+ }
+age         = _{ var | v_u_age }
+v_place     = { "(" ~ person ~ ")" }
+place       = _{ var | v_place | v_name }
+WHITESPACE  = _{ " " | "\t" | NEWLINE }
+"#;
+
+#[test]
+fn gets_the_canonical_text() {
+    let db = DynamicDB::from_grammar(GRAMMAR).unwrap();
+    let told = db.tell("susan  is 30\nand lives in paris ◊ john is 40 and lives in (susan) ◊");
+    assert_eq!(
+        db.get(told[0].id).as_deref(),
+        Some("susan is 30 and lives in paris")
+    );
+    assert_eq!(
+        db.get(told[1].id).as_deref(),
+        Some("john is 40 and lives in (susan)")
+    );
+    assert_eq!(db.get(FactId(0)), None);
+}
+
+#[test]
+fn ids_do_not_depend_on_what_else_was_told() {
+    let first = DynamicDB::from_grammar(GRAMMAR).unwrap();
+    let second = DynamicDB::from_grammar(GRAMMAR).unwrap();
+    let told = first.tell("susan is 30 and lives in (john) ◊");
+    second.tell("john is 40 and lives in rome ◊ sue is 20 and lives in (susan) ◊");
+    assert_eq!(
+        second.tell("susan is 30 and lives in ( john ) ◊")[0].id,
+        told[0].id
+    );
+}
+
+#[test]
+fn ids_are_kept_when_the_db_is_dumped_and_loaded() {
+    let db = DynamicDB::from_grammar(GRAMMAR).unwrap();
+    let told = db.tell("susan is 30 and lives in paris ◊ john is 40 and lives in (susan) ◊");
+    let mut dumped = vec![];
+    db.dump(&mut dumped).unwrap();
+    let loaded = DynamicDB::from_grammar(GRAMMAR).unwrap();
+    loaded.tell("peter is 50 and lives in (john) ◊");
+    loaded.tell(std::str::from_utf8(&dumped).unwrap());
+    for told in told {
+        assert_eq!(loaded.get(told.id), db.get(told.id));
+    }
+}
+
+#[test]
+fn forgets_sentences() {
+    let db = DynamicDB::from_grammar(GRAMMAR).unwrap();
+    let told = db.tell("john is 40 and lives in (susan) ◊ john is 40 and lives in (peter) ◊");
+    assert!(db.forget(told[0].id));
+    assert_eq!(db.get(told[0].id), None);
+    assert!(!db.forget(told[0].id));
+    assert_eq!(db.ask("john is 40 and lives in (susan) ◊").len(), 0);
+    assert_eq!(db.ask("john is 40 and lives in X1 ◊").len(), 1);
+    assert!(db.get(told[1].id).is_some());
+    assert_eq!(db.iter_facts().count(), 1);
+}
+
+#[test]
+fn replaced_sentences_are_forgotten() {
+    let db = DynamicDB::from_grammar(GRAMMAR).unwrap();
+    let old = db.tell("susan is 30 and lives in paris ◊ john is 30 and lives in paris ◊");
+    let new = db.tell("susan is 31 and lives in paris ◊");
+    assert_eq!(new[0].status, TellStatus::Replaced);
+    assert_eq!(db.get(old[0].id), None);
+    assert!(!db.forget(old[0].id));
+    assert!(db.get(old[1].id).is_some());
+    let facts: Vec<String> = db.iter_facts().collect();
+    assert_eq!(facts.len(), 2);
+    assert!(facts.contains(&"susan is 31 and lives in paris".to_string()));
+}
+
+#[test]
+fn counts_the_sentences_in_the_db() {
+    let db = DynamicDB::from_grammar(GRAMMAR).unwrap();
+    assert!(db.is_empty());
+    let told = db.tell(
+        "susan is 30 and lives in paris ◊ john is 40 and lives in (susan) ◊ susan is 30 and lives in paris ◊",
+    );
+    assert_eq!(db.len(), 2);
+    db.tell("susan is 31 and lives in paris ◊");
+    assert_eq!(db.len(), 2);
+    db.forget(told[1].id);
+    let stats = db.stats();
+    assert_eq!(stats.sentences, 1);
+    assert_eq!(stats.productions.get("fact"), Some(&1));
+    assert_eq!(stats.productions.len(), 1);
+}
+
+#[test]
+fn iterates_over_the_sentences_in_the_db() {
+    let db = DynamicDB::from_grammar(GRAMMAR).unwrap();
+    let told = db.tell("john is 40 and lives in ( susan ) ◊ susan is 30 and\tlives in paris ◊");
+    let mut facts: Vec<String> = db.iter_facts().collect();
+    facts.sort();
+    assert_eq!(
+        facts,
+        [
+            "john is 40 and lives in ( susan )",
+            "susan is 30 and lives in paris"
+        ]
+    );
+    for told in told {
+        assert!(facts.contains(&db.get(told.id).unwrap()));
+    }
+}
+
+const TREES: &str = r#"
+fact        = { v_branch }
+var         = @{ "X" ~ ASCII_DIGIT* }
+v_leaf      = @{ ASCII_DIGIT+ }
+leaf        = _{ var | v_leaf }
+v_branch    = { "(" ~ leaf ~ any* ~ ")" }
+branch      = _{ var | v_branch }
+any         = _{ leaf | branch }
+WHITESPACE  = _{ " " | "\t" | NEWLINE }
+"#;
+
+/// A xorshift generator, so that the random trees are the same in every run.
+struct Random(u64);
+
+impl Random {
+    fn below(&mut self, top: u64) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0 % top
+    }
+    fn tree(&mut self, depth: u64) -> String {
+        let mut tree = format!("({}", self.below(3));
+        for _ in 0..self.below(3) {
+            if depth > 0 && self.below(2) == 0 {
+                tree.push(' ');
+                tree.push_str(&self.tree(depth - 1));
+            } else {
+                tree.push_str(&format!(" {}", self.below(3)));
+            }
+        }
+        tree.push(')');
+        tree
+    }
+}
+
+#[test]
+fn sentences_that_are_prefixes_of_others_are_forgotten() {
+    let db = DynamicDB::from_grammar(TREES).unwrap();
+    let told = db.tell("(1) ◊ (1 0) ◊");
+    assert!(db.forget(told[0].id));
+    assert_eq!(db.get(told[0].id), None);
+    assert_eq!(db.ask("(1) ◊").len(), 0);
+    assert_eq!(db.ask("(1 0) ◊").len(), 1);
+    assert_eq!(db.len(), 1);
+    db.tell("(1) ◊");
+    assert!(db.forget(db.tell("(1 0) ◊")[0].id));
+    assert_eq!(db.ask("(1) ◊").len(), 1);
+    assert_eq!(db.iter_facts().collect::<Vec<_>>(), ["(1)"]);
+}
+
+#[test]
+fn random_trees_are_told_and_forgotten_consistently() {
+    let db = DynamicDB::from_grammar(TREES).unwrap();
+    let mut random = Random(0x2545_f491_4f6c_dd1d);
+    let mut held = std::collections::BTreeMap::new();
+    let mut ids = vec![];
+    for _ in 0..800 {
+        let told = db.tell(&format!("{} ◊", random.tree(3)))[0];
+        let text = db.get(told.id).expect("told sentence");
+        let present = told.status == TellStatus::Present;
+        assert_eq!(held.insert(told.id, text).is_some(), present);
+        ids.push(told.id);
+    }
+    let mut forgotten = vec![];
+    for id in ids.iter().step_by(3) {
+        if let Some(text) = held.remove(id) {
+            assert!(db.forget(*id), "{}", text);
+            forgotten.push(text);
+        } else {
+            assert!(!db.forget(*id));
+        }
+    }
+    for text in forgotten.iter().step_by(2) {
+        let told = db.tell(&format!("{} ◊", text))[0];
+        assert_eq!(told.status, TellStatus::Added);
+        held.insert(told.id, text.clone());
+    }
+    assert_eq!(db.len(), held.len());
+    let mut facts: Vec<String> = db.iter_facts().collect();
+    let mut texts: Vec<String> = held.values().cloned().collect();
+    facts.sort();
+    texts.sort();
+    assert_eq!(facts, texts);
+    for id in ids {
+        let text = held.get(&id);
+        assert_eq!(db.get(id).as_ref(), text);
+        if let Some(text) = text {
+            assert_eq!(db.ask(&format!("{} ◊", text)).len(), 1, "{}", text);
+        }
+    }
+    let known: std::collections::BTreeSet<&String> = held.values().collect();
+    for text in &known {
+        let rest = &text[2..];
+        let same = (0..3).filter(|leaf| known.contains(&format!("({}{}", leaf, rest)));
+        assert_eq!(db.ask(&format!("(X1{} ◊", rest)).len(), same.count(), "{}", text);
+    }
+}
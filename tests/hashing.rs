@@ -0,0 +1,86 @@
+// Copyright (c) 2020 by Enrique Pérez Arnaud <enrique at cazalla.net>
+//
+// This file is part of the modus_ponens project.
+// http://www.modus_ponens.net
+//
+// The modus_ponens project is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// The modus_ponens project is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with any part of the modus_ponens project.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use std::hash::{BuildHasher, Hasher};
+
+use uindex::dynamic::DynamicDB;
+use uindex::hashing::MPBuildHasher;
+use uindex::kbase::DataBase;
+use uindex::matching::MPMatching;
+
+const GRAMMAR: &str = r#"
+fact        = { name ~ "ISA" ~ name }
+var         = @{ "X" ~ ('0'..'9')* }
+v_name      = @{ ASCII_ALPHANUMERIC+ }
+name        = _{ var | v_name }
+WHITESPACE  = _{ " " | "\t" | NEWLINE }
+"#;
+
+fn answers(matchings: Vec<MPMatching>) -> Vec<String> {
+    matchings
+        .iter()
+        .map(|matching| {
+            let mut values: Vec<&str> = matching.values().map(|v| v.text.as_str()).collect();
+            values.sort();
+            values.join(" ")
+        })
+        .collect()
+}
+
+fn told_with_seed(seed: u64) -> Vec<String> {
+    let db = DynamicDB::from_grammar_with_hasher(GRAMMAR, MPBuildHasher::with_seed(seed)).unwrap();
+    for i in 0..200 {
+        db.tell(&format!("thing{} ISA set{} ◊", i, i % 3));
+    }
+    answers(db.ask("X1 ISA X2 ◊"))
+}
+
+#[cfg(not(any(feature = "fxhash", feature = "ahash")))]
+#[test]
+fn default_hasher_is_fnv_1a() {
+    let mut hasher = MPBuildHasher::default().build_hasher();
+    hasher.write(b"a");
+    assert_eq!(hasher.finish(), 0xaf63_dc4c_8601_ec8c);
+    let mut hasher = MPBuildHasher::default().build_hasher();
+    hasher.write(b"foobar");
+    assert_eq!(hasher.finish(), 0x8594_4171_f739_67e8);
+}
+
+#[test]
+fn seeds_change_the_hashes() {
+    let hash = |seed| {
+        let mut hasher = MPBuildHasher::with_seed(seed).build_hasher();
+        hasher.write(b"foobar");
+        hasher.finish()
+    };
+    assert_eq!(hash(7), hash(7));
+    assert_ne!(hash(7), hash(8));
+}
+
+#[test]
+fn answers_are_reproducible_for_a_seed() {
+    let answers = told_with_seed(42);
+    assert_eq!(answers.len(), 200);
+    assert_eq!(answers, told_with_seed(42));
+    let mut sorted = answers.clone();
+    sorted.sort();
+    let mut other = told_with_seed(43);
+    other.sort();
+    assert_eq!(sorted, other);
+}
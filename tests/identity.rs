@@ -0,0 +1,129 @@
+// Copyright (c) 2020 by Enrique Pérez Arnaud <enrique at cazalla.net>
+//
+// This file is part of the modus_ponens project.
+// http://www.modus_ponens.net
+//
+// The modus_ponens project is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// The modus_ponens project is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with any part of the modus_ponens project.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashSet;
+
+use uindex::dynamic::DynamicDB;
+use uindex::kbase::DataBase;
+use uindex::lexicon::Lexicon;
+
+const GRAMMAR: &str = r#"
+fact        = { name ~ "ISA" ~ name }
+var         = @{ "X" ~ ('0'..'9')* }
+v_name      = @{ ASCII_ALPHANUMERIC+ }
+name        = _{ var | v_name }
+WHITESPACE  = _{ " " | "\t" | NEWLINE }
+"#;
+
+#[test]
+fn lexicon_ids_are_unique() {
+    let lexicon = Lexicon::new();
+    let mut ids = HashSet::new();
+    for i in 0..10_000 {
+        assert!(ids.insert(lexicon.intern_name(&format!("name{}", i))));
+    }
+    for i in 0..10_000 {
+        assert!(ids.insert(lexicon.intern_key(&[i, i + 1])));
+    }
+    assert!(!ids.contains(&0));
+    assert_eq!(lexicon.intern_name("name42"), lexicon.intern_name("name42"));
+    assert_eq!(lexicon.intern_key(&[42, 43]), lexicon.intern_key(&[42, 43]));
+}
+
+#[test]
+fn rule_names_are_given_back() {
+    let lexicon = Lexicon::new();
+    let id = lexicon.intern_rule_name("v_name");
+    assert_eq!(lexicon.rule_name(id), Some("v_name"));
+    assert_eq!(lexicon.intern_name("v_name"), id);
+    assert_eq!(lexicon.rule_name(lexicon.intern_name("other")), None);
+}
+
+#[test]
+fn distinct_facts_are_not_merged() {
+    let db = DynamicDB::from_grammar(GRAMMAR).unwrap();
+    let facts: Vec<String> = (0..2_000)
+        .map(|i| format!("thing{} ISA set{} ◊", i, i % 7))
+        .collect();
+    for fact in &facts {
+        db.tell(fact);
+    }
+    for fact in &facts {
+        assert_eq!(db.ask(fact).len(), 1, "{}", fact);
+    }
+    assert_eq!(db.ask("X1 ISA X2 ◊").len(), facts.len());
+    assert_eq!(
+        db.ask("X1 ISA set3 ◊").len(),
+        (0..2_000).filter(|i| i % 7 == 3).count()
+    );
+    assert_eq!(db.ask("thing3 ISA set4 ◊").len(), 0);
+}
+
+const TREES: &str = r#"
+fact        = { v_branch }
+var         = @{ "X" ~ ASCII_DIGIT* }
+v_leaf      = @{ ASCII_DIGIT+ }
+leaf        = _{ var | v_leaf }
+v_branch    = { "(" ~ leaf ~ any* ~ ")" }
+branch      = _{ var | v_branch }
+any         = _{ leaf | branch }
+WHITESPACE  = _{ " " | "\t" | NEWLINE }
+"#;
+
+#[test]
+fn formatting_does_not_change_identity() {
+    let db = DynamicDB::from_grammar(TREES).unwrap();
+    db.tell("(6 (60 617)) ◊");
+    assert_eq!(db.ask("(6  (60   617)) ◊").len(), 1);
+    assert_eq!(db.ask("( 6\n\t(60 617 ) ) ◊").len(), 1);
+    assert_eq!(db.ask("(6 X1) ◊").len(), 1);
+    assert_eq!(db.ask("(6 (60 X1)) ◊").len(), 1);
+    db.tell("(6   ( 60 617 ))◊");
+    assert_eq!(db.ask("(6 X1) ◊").len(), 1);
+    assert_eq!(db.ask("(6 (60 618)) ◊").len(), 0);
+}
+
+#[test]
+fn variables_match_whole_subtrees_whatever_their_formatting() {
+    let db = DynamicDB::from_grammar(TREES).unwrap();
+    db.tell("(6 (60 617)) ◊ (8 (60   617)) ◊ (9 (60 618)) ◊");
+    assert_eq!(db.ask("(6 X1) ◊ (8 X1) ◊").len(), 1);
+    assert_eq!(db.ask("(6 X1) ◊ (9 X1) ◊").len(), 0);
+}
+
+#[test]
+fn variables_match_subtrees_followed_by_more_of_the_sentence() {
+    let db = DynamicDB::from_grammar(TREES).unwrap();
+    db.tell("(6 (60 617) 7) ◊ (6 (60 618) 7) ◊ (6 (60 617) 8) ◊");
+    assert_eq!(db.ask("(6 X1 7) ◊").len(), 2);
+    assert_eq!(db.ask("(6 X1 X2) ◊").len(), 3);
+    assert_eq!(db.ask("(6 (60 X1) X2) ◊").len(), 3);
+    assert_eq!(db.ask("(6 X1 7) ◊ (6 X1 8) ◊").len(), 1);
+    assert_eq!(db.ask("(6 X1) ◊").len(), 0);
+    assert_eq!(db.ask("(X1 X2 7) ◊").len(), 2);
+}
+
+#[test]
+fn variables_only_match_values_in_their_position() {
+    let db = DynamicDB::from_grammar(TREES).unwrap();
+    db.tell("(6 (60 617)) ◊");
+    // `60` and `617` are under `(60 617)`, not in its place
+    assert_eq!(db.ask("(6 X1) ◊").len(), 1);
+    assert_eq!(db.ask("(X1 X2) ◊").len(), 1);
+}
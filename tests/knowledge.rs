@@ -0,0 +1,163 @@
+// Copyright (c) 2020 by Enrique Pérez Arnaud <enrique at cazalla.net>
+//
+// This file is part of the modus_ponens project.
+// http://www.modus_ponens.net
+//
+// The modus_ponens project is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// The modus_ponens project is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with any part of the modus_ponens project.
+// If not, see <http://www.gnu.org/licenses/>.
+
+extern crate uindex;
+#[macro_use]
+extern crate uindex_derive;
+#[macro_use]
+extern crate pest_derive;
+
+use uindex::dynamic::{DynamicDB, DynamicParser, KnowledgeSyntax};
+use uindex::kbase::{DBGen, DataBase};
+use uindex::kparser;
+use uindex::sentence::SentenceParser;
+
+#[derive(DBGen)]
+#[grammar_inline = r#"
+fact        = { name ~ name ~ name }
+var         = @{ "X" ~ ASCII_DIGIT* }
+v_name      = @{ ASCII_ALPHA+ }
+name        = _{ var | v_name }
+WHITESPACE  = _{ " " | "\t" }
+"#]
+#[terminator = "\n"]
+#[terminator = ";"]
+pub struct Records;
+
+#[derive(DBGen)]
+#[grammar_inline = r#"
+fact        = { name ~ name ~ name }
+var         = @{ "X" ~ ASCII_DIGIT* }
+v_name      = @{ ASCII_ALPHA+ }
+name        = _{ var | v_name }
+WHITESPACE  = _{ " " | "\t" }
+"#]
+#[terminator = "\n"]
+pub struct Lines;
+
+#[derive(DBGen)]
+#[grammar_inline = r#"
+fact        = { name ~ name ~ name }
+var         = @{ "X" ~ ASCII_DIGIT* }
+v_name      = @{ ASCII_ALPHA+ }
+name        = _{ var | v_name }
+WHITESPACE  = _{ " " | "\t" | NEWLINE }
+"#]
+#[terminator = ";"]
+#[comment = r##""#" ~ (!NEWLINE ~ ANY)*"##]
+pub struct Statements;
+
+#[derive(DBGen)]
+#[grammar_inline = r#"
+fact        = { name ~ "says" ~ quote }
+var         = @{ "X" ~ ASCII_DIGIT* }
+v_name      = @{ ASCII_ALPHA+ }
+name        = _{ var | v_name }
+v_quote     = @{ "\"" ~ (!"\"" ~ ANY)* ~ "\"" }
+quote       = _{ var | v_quote }
+WHITESPACE  = _{ " " | "\t" | NEWLINE }
+"#]
+#[string]
+pub struct Quotes;
+
+#[test]
+fn newline_terminated_records() {
+    let db = Lines::gen_db();
+    assert_eq!(db.tell("a b c\nd e f\n\ng h i\n").len(), 3);
+    assert_eq!(db.ask("X1 X2 X3\n").len(), 3);
+    assert_eq!(db.ask("d e f").len(), 1);
+    let db = Lines::gen_db();
+    assert_eq!(db.tell("x y z\r\nk l m\r\n").len(), 2);
+    assert_eq!(db.ask("k l m").len(), 1);
+}
+
+#[test]
+fn several_terminators() {
+    let db = Records::gen_db();
+    assert_eq!(db.tell("x y z\nk l m;").len(), 2);
+    assert_eq!(db.tell("a b c\nd e f;g h i").len(), 3);
+    assert_eq!(db.ask("d e f;").len(), 1);
+    assert_eq!(db.ask("X1 X2 X3;").len(), 5);
+}
+
+#[test]
+fn statements_span_lines() {
+    let db = Statements::gen_db();
+    let told = db.tell("# people\na b\n  c;\nd e f ; # more\ng h i;");
+    assert_eq!(told.len(), 3);
+    assert_eq!(db.ask("a b c;").len(), 1);
+    assert_eq!(db.ask("X1 X2 X3;").len(), 3);
+}
+
+#[test]
+fn default_grammar() {
+    let grammar = kparser::knowledge_grammar(&[], None, None, None);
+    assert!(grammar.contains(r#"fact = ${ (!("◊" | "<>") ~ ANY)+ }"#));
+    assert!(!grammar.contains("string"));
+    assert!(grammar.contains(r#"WHITESPACE = _{ !("◊" | "<>") ~ (" " | "\t" | NEWLINE) }"#));
+    let grammar =
+        kparser::knowledge_grammar(&[";".into(), "\n".into(), ";;".into()], None, None, None);
+    assert!(grammar.contains(r#"!(";;" | ";" | "\n")"#));
+}
+
+#[test]
+fn quotes_are_plain_text_by_default() {
+    let grammar = "fact = { (!\"◊\" ~ ANY)+ }\nvar = @{ \"X\" ~ ASCII_DIGIT* }";
+    let parser = DynamicParser::from_grammar(grammar).unwrap();
+    let facts = parser.parse_text("5\" screen ◊ 7\" tablet ◊").unwrap().facts;
+    assert_eq!(facts, ["5\" screen", "7\" tablet"]);
+}
+
+#[test]
+fn terminators_within_strings() {
+    let db = Quotes::gen_db();
+    let told = db.tell(r#"susan says "see you <> soon ◊" ◊ john says "bye" ◊"#);
+    assert_eq!(told.len(), 2);
+    assert_eq!(db.ask("susan says X1 ◊").len(), 1);
+    assert_eq!(db.ask(r#"X1 says "see you <> soon ◊" ◊"#).len(), 1);
+}
+
+#[test]
+fn terminators_within_strings_at_runtime() {
+    let grammar = r#"
+fact        = { name ~ "says" ~ quote }
+var         = @{ "X" ~ ASCII_DIGIT* }
+v_name      = @{ ASCII_ALPHA+ }
+name        = _{ var | v_name }
+v_quote     = @{ "\"" ~ (!"\"" ~ ANY)* ~ "\"" }
+quote       = _{ var | v_quote }
+WHITESPACE  = _{ " " | "\t" | NEWLINE }
+"#;
+    let db =
+        DynamicDB::from_grammar_with_syntax(grammar, &KnowledgeSyntax::with_strings()).unwrap();
+    let told = db.tell(r#"susan says "see you <> soon ◊" ◊ /* a ◊ */ john says "bye" ◊"#);
+    assert_eq!(told.len(), 2);
+    assert_eq!(db.ask(r#"X1 says "see you <> soon ◊" ◊"#).len(), 1);
+    let syntax = KnowledgeSyntax {
+        comment: Some(r##""#" ~ (!NEWLINE ~ ANY)*"##.to_string()),
+        ..KnowledgeSyntax::with_strings()
+    };
+    let db = DynamicDB::from_grammar_with_syntax(grammar, &syntax).unwrap();
+    assert_eq!(db.tell("# people ◊\nsusan says \"#1 ◊\" ◊").len(), 1);
+    let syntax = KnowledgeSyntax {
+        string: Some("\"unclosed".to_string()),
+        ..KnowledgeSyntax::default()
+    };
+    assert!(DynamicDB::from_grammar_with_syntax(grammar, &syntax).is_err());
+}
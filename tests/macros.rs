@@ -0,0 +1,118 @@
+// Copyright (c) 2020 by Enrique Pérez Arnaud <enrique at cazalla.net>
+//
+// This file is part of the modus_ponens project.
+// http://www.modus_ponens.net
+//
+// The modus_ponens project is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// The modus_ponens project is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with any part of the modus_ponens project.
+// If not, see <http://www.gnu.org/licenses/>.
+
+extern crate uindex;
+#[macro_use]
+extern crate uindex_derive;
+#[macro_use]
+extern crate pest_derive;
+
+use quote::quote;
+use uindex::kbase::{DBGen, DataBase};
+use uindex::{fact, query};
+
+#[derive(DBGen)]
+#[grammar_inline = r#"
+fact        = { name ~ "likes" ~ name }
+var         = @{ "X" ~ ASCII_DIGIT* }
+v_name      = @{ ASCII_ALPHA+ }
+name        = _{ var | v_name }
+WHITESPACE  = _{ " " | "\t" | NEWLINE }
+"#]
+pub struct Likes;
+
+#[derive(DBGen)]
+#[grammar_inline = r#"
+fact        = { name ~ "likes" ~ name }
+var         = @{ "X" ~ ASCII_DIGIT* }
+v_name      = @{ ASCII_ALPHA+ }
+name        = _{ var | v_name }
+WHITESPACE  = _{ " " | "\t" }
+"#]
+#[terminator = "\n"]
+pub struct Lines;
+
+#[test]
+fn facts_expand_to_their_text() {
+    let db = Likes::gen_db();
+    let text = fact!(Likes, "susan likes oranges ◊ john likes apples ◊");
+    assert_eq!(text, "susan likes oranges ◊ john likes apples ◊");
+    db.tell(text);
+    assert_eq!(db.ask("X1 likes X2 ◊").len(), 2);
+}
+
+#[test]
+fn queries_have_a_field_for_each_variable() {
+    let db = Likes::gen_db();
+    db.tell("susan likes oranges ◊ john likes apples ◊ susan likes apples ◊");
+    let query = query!(Likes, "susan likes X1 ◊");
+    assert_eq!(query.text(), "susan likes X1 ◊");
+    let mut liked: Vec<String> = query.ask(&db).into_iter().map(|a| a.X1).collect();
+    liked.sort();
+    assert_eq!(liked, ["apples", "oranges"]);
+    let answers = query!(Likes, "X1 likes X2 ◊ X3 likes X2 ◊").ask(&db);
+    assert!(answers
+        .iter()
+        .any(|a| a.X1 == "john" && a.X2 == "apples" && a.X3 == "susan"));
+}
+
+#[test]
+fn knowledge_syntax_of_the_db() {
+    let db = Lines::gen_db();
+    db.tell(fact!(Lines, "susan likes oranges\njohn likes apples\n"));
+    let answers = query!(Lines, "X1 likes apples\n").ask(&db);
+    assert_eq!(answers.len(), 1);
+    assert_eq!(answers[0].X1, "john");
+}
+
+fn check(kind: &str, text: &str) -> String {
+    let kind = syn::Ident::new(kind, proc_macro2::Span::call_site());
+    let grammar = "fact = { name ~ \"likes\" ~ name }\nvar = @{ \"X\" ~ ASCII_DIGIT* }\n\
+                   v_name = @{ ASCII_ALPHA+ }\nname = _{ var | v_name }\nWHITESPACE = _{ \" \" }";
+    uindex::check_sentences(quote! { #[grammar_inline = #grammar] #kind #text }).to_string()
+}
+
+#[test]
+fn invalid_sentences_are_compile_errors() {
+    assert!(!check("fact", "susan likes oranges ◊").contains("compile_error"));
+    assert!(check("fact", "susan loves oranges ◊").contains("cannot parse `susan loves oranges`"));
+    assert!(check("fact", "X1 likes oranges ◊").contains("facts cannot have variables, found X1"));
+    assert!(check("query", "X1 likes oranges ◊").contains("struct Answer"));
+    assert!(check("other", "susan likes oranges ◊").contains("expected `fact` or `query`"));
+}
+
+#[test]
+fn sentences_with_renamed_variables() {
+    let grammar = "fact = { name ~ \"likes\" ~ name }\nvariable = @{ \"?\" ~ ASCII_ALPHA+ }\n\
+                   r_name = @{ ASCII_ALPHA+ }\nname = _{ variable | r_name }\nWHITESPACE = _{ \" \" }";
+    let check = |kind: &str, text: &str| {
+        let kind = syn::Ident::new(kind, proc_macro2::Span::call_site());
+        uindex::check_sentences(quote! {
+            #[grammar_inline = #grammar]
+            #[var_rule = "variable"]
+            #[var_range_prefix = "r_"]
+            #kind #text
+        })
+        .to_string()
+    };
+    assert!(!check("fact", "susan likes oranges ◊").contains("compile_error"));
+    assert!(
+        check("fact", "?who likes oranges ◊").contains("facts cannot have variables, found ?who")
+    );
+}
@@ -0,0 +1,107 @@
+// Copyright (c) 2020 by Enrique Pérez Arnaud <enrique at cazalla.net>
+//
+// This file is part of the modus_ponens project.
+// http://www.modus_ponens.net
+//
+// The modus_ponens project is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// The modus_ponens project is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with any part of the modus_ponens project.
+// If not, see <http://www.gnu.org/licenses/>.
+
+extern crate uindex;
+#[macro_use]
+extern crate uindex_derive;
+#[macro_use]
+extern crate pest_derive;
+
+use pest_meta::ast::RuleType;
+use uindex::dynamic::DynamicDB;
+use uindex::kbase::DBGen;
+use uindex::schema::{Schema, SchemaError};
+
+const GRAMMAR: &str = r#"
+fact        = { person ~ "is" ~ age }
+var         = @{ "X" ~ ASCII_DIGIT* }
+v_name      = @{ ASCII_ALPHA+ }
+person      = _{ var | v_name }
+v_u_age     = @{ ASCII_DIGIT+ }
+age         = _{ var | v_u_age }
+WHITESPACE  = _{ " " | "\t" | NEWLINE }
+"#;
+
+#[derive(DBGen)]
+#[grammar_inline = r#"
+fact        = { person ~ "is" ~ age }
+var         = @{ "X" ~ ASCII_DIGIT* }
+v_name      = @{ ASCII_ALPHA+ }
+person      = _{ var | v_name }
+v_u_age     = @{ ASCII_DIGIT+ }
+age         = _{ var | v_u_age }
+WHITESPACE  = _{ " " | "\t" | NEWLINE }
+"#]
+pub struct Ages;
+
+fn names<'s>(productions: impl Iterator<Item = &'s uindex::schema::Production>) -> Vec<&'s str> {
+    productions.map(|p| p.name.as_str()).collect()
+}
+
+#[test]
+fn describes_the_grammar() {
+    let schema = Schema::from_grammar(GRAMMAR).unwrap();
+    assert_eq!(
+        names(schema.productions.iter()),
+        [
+            "fact",
+            "var",
+            "v_name",
+            "person",
+            "v_u_age",
+            "age",
+            "WHITESPACE"
+        ]
+    );
+    assert_eq!(schema.var_rule().unwrap().name, "var");
+    assert_eq!(names(schema.var_range()), ["v_name", "v_u_age"]);
+    assert_eq!(names(schema.unique()), ["v_u_age"]);
+    let fact = schema.fact().unwrap();
+    assert_eq!(fact.ty, RuleType::Normal);
+    assert_eq!(fact.references(), ["person", "age"]);
+    assert_eq!(schema.production("person").unwrap().ty, RuleType::Silent);
+    assert!(schema.check().is_ok());
+}
+
+#[test]
+fn dbs_describe_their_grammar() {
+    let derived = Ages::gen_db();
+    let dynamic = DynamicDB::from_grammar(GRAMMAR).unwrap();
+    for schema in &[derived.schema(), dynamic.schema()] {
+        assert_eq!(names(schema.unique()), ["v_u_age"]);
+        assert_eq!(schema.fact().unwrap().references(), ["person", "age"]);
+    }
+}
+
+#[test]
+fn checks_the_conventions() {
+    let schema = Schema::from_grammar("sentence = { v_name }\nv_name = @{ ASCII_ALPHA+ }").unwrap();
+    assert_eq!(
+        schema.check(),
+        Err(vec![SchemaError::MissingFact, SchemaError::MissingVar])
+    );
+    let schema = Schema::from_grammar(
+        "fact = { v_name ~ v_age }\nvar = @{ \"X\" }\nv_name = @{ ASCII_ALPHA+ }\nv_age = @{ ASCII_DIGIT+ }\nname = _{ var | v_name }",
+    )
+    .unwrap();
+    assert_eq!(
+        schema.check(),
+        Err(vec![SchemaError::MissingVarAlternative("v_age".into())])
+    );
+}
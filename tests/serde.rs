@@ -0,0 +1,93 @@
+// Copyright (c) 2020 by Enrique Pérez Arnaud <enrique at cazalla.net>
+//
+// This file is part of the modus_ponens project.
+// http://www.modus_ponens.net
+//
+// The modus_ponens project is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// The modus_ponens project is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with any part of the modus_ponens project.
+// If not, see <http://www.gnu.org/licenses/>.
+
+#![cfg(feature = "serde")]
+
+use serde_json::json;
+use uindex::bindings::Bindings;
+use uindex::dynamic::DynamicDB;
+use uindex::kbase::{DataBase, Stats};
+
+const GRAMMAR: &str = r#"
+fact        = { person ~ "lives" ~ "in" ~ place }
+var         = @{ "X" ~ ASCII_DIGIT* }
+v_name      = @{ ASCII_ALPHA+ }
+person      = _{ var | v_name }
+v_address   = { "(" ~ street ~ number ~ ")" }
+v_street    = @{ ASCII_ALPHA+ }
+street      = _{ var | v_street }
+v_number    = @{ ASCII_DIGIT+ }
+number      = _{ var | v_number }
+place       = _{ var | v_address | v_name }
+WHITESPACE  = _{ " " | "\t" | NEWLINE }
+"#;
+
+#[test]
+fn bindings_serialize_as_maps() {
+    let db = DynamicDB::from_grammar(GRAMMAR).unwrap();
+    db.tell("susan lives in (main 5) ◊");
+    let answers = db.ask_bindings("X1 lives in X2 ◊");
+    assert_eq!(
+        serde_json::to_value(&answers[0]).unwrap(),
+        json!({
+            "X1": "susan",
+            "X2": {
+                "production": "v_address",
+                "text": "(main 5)",
+                "children": [
+                    {"production": "v_street", "text": "main", "children": []},
+                    {"production": "v_number", "text": "5", "children": []},
+                ],
+            },
+        })
+    );
+}
+
+#[test]
+fn bindings_round_trip() {
+    let db = DynamicDB::from_grammar(GRAMMAR).unwrap();
+    db.tell("susan lives in (main 5) ◊");
+    let answers = db.ask_bindings("X1 lives in X2 ◊");
+    let json = serde_json::to_string(&answers[0]).unwrap();
+    let bindings: Bindings = serde_json::from_str(&json).unwrap();
+    // only non terminal values keep their production
+    assert_eq!(bindings.get("X1"), Some("susan"));
+    assert_eq!(bindings.production("X1"), Some(""));
+    assert_eq!(bindings.binding("X2"), answers[0].binding("X2"));
+}
+
+#[test]
+fn sentences_from_serde_data() {
+    let db = DynamicDB::from_grammar(GRAMMAR).unwrap();
+    let bindings: Bindings = serde_json::from_str(r#"{"X1": "john", "X2": "(high 7)"}"#).unwrap();
+    assert_eq!(bindings.get("X2"), Some("(high 7)"));
+    let text = db.instantiate("X1 lives in X2 ◊ X1 lives in X3 ◊", &bindings);
+    assert_eq!(text, "john lives in (high 7) ◊ john lives in X3 ◊");
+    db.tell(&db.instantiate("X1 lives in X2 ◊", &bindings));
+    assert_eq!(db.ask("john lives in (high 7) ◊").len(), 1);
+}
+
+#[test]
+fn stats_serialize() {
+    let db = DynamicDB::from_grammar(GRAMMAR).unwrap();
+    db.tell("susan lives in paris ◊");
+    let stats: Stats = serde_json::from_value(serde_json::to_value(db.stats()).unwrap()).unwrap();
+    assert_eq!(stats, db.stats());
+    assert_eq!(stats.sentences, 1);
+}
@@ -0,0 +1,191 @@
+// Copyright (c) 2020 by Enrique Pérez Arnaud <enrique at cazalla.net>
+//
+// This file is part of the modus_ponens project.
+// http://www.modus_ponens.net
+//
+// The modus_ponens project is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// The modus_ponens project is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with any part of the modus_ponens project.
+// If not, see <http://www.gnu.org/licenses/>.
+
+extern crate uindex;
+
+use std::io::{self, BufReader, Read};
+
+use uindex::dynamic::DynamicDB;
+use uindex::stream::{Progress, TellError};
+
+const GRAMMAR: &str = r#"
+fact        = { person ~ "is" ~ age ~ "and" ~ "likes" ~ fruit }
+var         = @{ "X" ~ ASCII_DIGIT* }
+v_name      = @{ ASCII_ALPHA+ }
+person      = _{ var | v_name }
+fruit       = _{ var | v_name }
+v_u_age     = @{ ASCII_DIGIT+ }
+age         = _{ var | v_u_age }
+WHITESPACE  = _{ " " | "\t" | NEWLINE }
+"#;
+
+/// Sentences about as many different people.
+fn knowledge(people: usize) -> String {
+    (0..people)
+        .map(|n| {
+            let name: String = n
+                .to_string()
+                .bytes()
+                .map(|digit| (digit - b'0' + b'a') as char)
+                .collect();
+            format!("{} is {} and likes pears ◊\n", name, n)
+        })
+        .collect()
+}
+
+/// Fails with an error once it has given `ok` bytes.
+struct Failing<'k> {
+    knowledge: &'k [u8],
+    ok: usize,
+}
+
+impl Read for Failing<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.ok == 0 {
+            return Err(io::Error::new(io::ErrorKind::Other, "broken"));
+        }
+        let len = buf.len().min(self.ok).min(self.knowledge.len());
+        buf[..len].copy_from_slice(&self.knowledge[..len]);
+        self.knowledge = &self.knowledge[len..];
+        self.ok -= len;
+        Ok(len)
+    }
+}
+
+#[test]
+fn tells_knowledge_read_a_byte_at_a_time() {
+    let db = DynamicDB::from_grammar(GRAMMAR).unwrap();
+    let knowledge = knowledge(20);
+    let reader = BufReader::with_capacity(1, knowledge.as_bytes());
+    let told = db.tell_reader(reader).unwrap();
+    assert_eq!(told.sentences, 20);
+    assert_eq!(told.bytes, knowledge.len() as u64);
+    assert_eq!(db.len(), 20);
+}
+
+#[test]
+fn tells_knowledge_bigger_than_a_read() {
+    let db = DynamicDB::from_grammar(GRAMMAR).unwrap();
+    let knowledge = knowledge(5000);
+    assert!(knowledge.len() > 1 << 17);
+    let mut reports = vec![];
+    let told = db
+        .tell_reader_with_progress(knowledge.as_bytes(), |progress: &Progress| {
+            reports.push(*progress)
+        })
+        .unwrap();
+    assert_eq!(told.sentences, 5000);
+    assert_eq!(db.len(), 5000);
+    assert!(reports.len() > 1);
+    assert!(reports.windows(2).all(|pair| pair[0].bytes < pair[1].bytes));
+    assert_eq!(reports.last(), Some(&told));
+}
+
+#[test]
+fn stops_at_the_first_sentence_that_cannot_be_parsed() {
+    let db = DynamicDB::from_grammar(GRAMMAR).unwrap();
+    let good = knowledge(10);
+    let knowledge = format!("{}susan is old and likes pears ◊\n{}", good, good);
+    for capacity in &[1, 7, 1 << 16] {
+        let reader = BufReader::with_capacity(*capacity, knowledge.as_bytes());
+        match db.tell_reader(reader) {
+            Err(TellError::Sentence { offset, .. }) => assert_eq!(offset, good.len() as u64),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+    assert_eq!(db.len(), 10);
+}
+
+#[test]
+fn reports_where_the_input_is_not_utf8() {
+    let db = DynamicDB::from_grammar(GRAMMAR).unwrap();
+    let good = knowledge(3);
+    let mut knowledge = good.clone().into_bytes();
+    knowledge.extend_from_slice(b"susan is 3\xff and likes pears \xe2\x97\x8a\n");
+    let reader = BufReader::with_capacity(2, knowledge.as_slice());
+    match db.tell_reader(reader) {
+        Err(TellError::Utf8 { offset }) => assert_eq!(offset, good.len() as u64 + 10),
+        other => panic!("unexpected {:?}", other),
+    }
+    assert_eq!(db.len(), 3);
+}
+
+#[test]
+fn reports_where_the_input_could_not_be_read() {
+    let db = DynamicDB::from_grammar(GRAMMAR).unwrap();
+    let knowledge = knowledge(10);
+    let reader = BufReader::with_capacity(
+        4,
+        Failing {
+            knowledge: knowledge.as_bytes(),
+            ok: 100,
+        },
+    );
+    match db.tell_reader(reader) {
+        Err(TellError::Io { offset, .. }) => assert_eq!(offset, 100),
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn imports_the_sentences_that_can_be_parsed() {
+    let knowledge = "susan is 30 and likes pears ◊\njohn is old and likes pears ◊\n\
+                     susan is 30 and likes pears ◊ susan is 31 and likes pears ◊\n  \
+                     mary likes pears ◊\n";
+    for capacity in &[1, 5, 1 << 16] {
+        let db = DynamicDB::from_grammar(GRAMMAR).unwrap();
+        let reader = BufReader::with_capacity(*capacity, knowledge.as_bytes());
+        let report = db.import_reader(reader).unwrap();
+        assert_eq!((report.added, report.present, report.replaced), (1, 1, 1));
+        assert_eq!(report.skipped.len(), 2);
+        let skipped = &report.skipped[0];
+        assert_eq!((skipped.line, skipped.column), (2, 1));
+        assert_eq!(skipped.offset, knowledge.find("john").unwrap() as u64);
+        assert_eq!(skipped.sentence, "john is old and likes pears");
+        let skipped = &report.skipped[1];
+        assert_eq!((skipped.line, skipped.column), (4, 3));
+        assert_eq!(skipped.sentence, "mary likes pears");
+        assert_eq!(db.len(), 1);
+        assert_eq!(db.check(knowledge), report.skipped);
+    }
+}
+
+#[test]
+fn skips_a_last_sentence_without_terminator() {
+    let db = DynamicDB::from_grammar(GRAMMAR).unwrap();
+    let report = db.import("susan is 30 and likes pears ◊\njohn is 40 and likes pears");
+    assert_eq!(report.added, 1);
+    assert_eq!(report.skipped.len(), 1);
+    let skipped = &report.skipped[0];
+    assert_eq!((skipped.line, skipped.column), (2, 1));
+    assert_eq!(skipped.sentence, "john is 40 and likes pears");
+    assert!(skipped.error.contains("expected a terminator"));
+}
+
+#[test]
+fn import_fails_only_if_the_input_cannot_be_read() {
+    let db = DynamicDB::from_grammar(GRAMMAR).unwrap();
+    let mut knowledge = b"susan is 30 and likes pears \xe2\x97\x8a\n".to_vec();
+    knowledge.extend_from_slice(b"john is \xff and likes pears \xe2\x97\x8a\n");
+    match db.import_reader(knowledge.as_slice()) {
+        Err(TellError::Utf8 { offset }) => assert_eq!(offset, 40),
+        other => panic!("unexpected {:?}", other),
+    }
+    assert_eq!(db.len(), 1);
+}
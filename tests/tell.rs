@@ -0,0 +1,111 @@
+// Copyright (c) 2020 by Enrique Pérez Arnaud <enrique at cazalla.net>
+//
+// This file is part of the modus_ponens project.
+// http://www.modus_ponens.net
+//
+// The modus_ponens project is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// The modus_ponens project is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with any part of the modus_ponens project.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use uindex::dynamic::DynamicDB;
+use uindex::kbase::{DataBase, TellStatus};
+
+const GRAMMAR: &str = r#"
+fact        = { person ~ "is" ~ age ~ "and" ~ "likes" ~ fruit }
+var         = @{ "X" ~ ASCII_DIGIT* }
+v_name      = @{ ASCII_ALPHA+ }
+person      = _{ var | v_name }
+fruit       = _{ var | v_name }
+v_u_age     = @{ ASCII_DIGIT+ }
+age         = _{ var | v_u_age }
+WHITESPACE  = _{ " " | "\t" | NEWLINE }
+"#;
+
+fn statuses(told: Vec<uindex::kbase::Told>) -> Vec<TellStatus> {
+    told.iter().map(|told| told.status).collect()
+}
+
+#[test]
+fn status_of_each_sentence() {
+    let db = DynamicDB::from_grammar(GRAMMAR).unwrap();
+    assert_eq!(
+        statuses(db.tell("susan is 30 and likes pears ◊ john is 40 and likes pears ◊")),
+        [TellStatus::Added, TellStatus::Added]
+    );
+    assert_eq!(
+        statuses(db.tell(
+            "susan is 30 and likes pears ◊ susan is 31 and likes pears ◊ susan is 31 and likes pears ◊"
+        )),
+        [TellStatus::Present, TellStatus::Replaced, TellStatus::Present]
+    );
+    assert_eq!(db.ask("susan is X1 and likes pears ◊").len(), 1);
+    assert_eq!(db.ask("susan is 31 and likes pears ◊").len(), 1);
+}
+
+#[test]
+fn sentences_after_a_duplicate_are_told() {
+    let db = DynamicDB::from_grammar(GRAMMAR).unwrap();
+    db.tell("susan is 30 and likes pears ◊");
+    let told = db.tell("susan is 30 and likes pears ◊ john is 40 and likes pears ◊");
+    assert_eq!(statuses(told), [TellStatus::Present, TellStatus::Added]);
+    assert_eq!(db.ask("X1 is X2 and likes pears ◊").len(), 2);
+}
+
+#[test]
+fn formatting_does_not_make_sentences_new() {
+    let db = DynamicDB::from_grammar(GRAMMAR).unwrap();
+    let added = db.tell("susan is 30 and likes pears ◊");
+    let present = db.tell("susan  is 30\nand likes   pears ◊");
+    assert_eq!(present[0].status, TellStatus::Present);
+    assert_eq!(present[0].id, added[0].id);
+}
+
+#[test]
+fn unique_values_are_replaced_only_in_their_own_sentences() {
+    let db = DynamicDB::from_grammar(GRAMMAR).unwrap();
+    db.tell("susan is 30 and likes pears ◊ john is 30 and likes pears ◊");
+    assert_eq!(
+        db.tell("susan is 31 and likes pears ◊")[0].status,
+        TellStatus::Replaced
+    );
+    assert_eq!(db.ask("john is 30 and likes pears ◊").len(), 1);
+    assert_eq!(db.ask("X1 is 30 and likes pears ◊").len(), 1);
+}
+
+#[test]
+fn sentences_that_are_prefixes_of_others_are_added_once() {
+    let db = DynamicDB::from_grammar(
+        r#"
+fact        = { v_branch }
+var         = @{ "X" ~ ASCII_DIGIT* }
+v_leaf      = @{ ASCII_DIGIT+ }
+leaf        = _{ var | v_leaf }
+v_branch    = { "(" ~ leaf ~ any* ~ ")" }
+branch      = _{ var | v_branch }
+any         = _{ leaf | branch }
+WHITESPACE  = _{ " " | "\t" | NEWLINE }
+"#,
+    )
+    .unwrap();
+    assert_eq!(
+        statuses(db.tell("(1 0) ◊ (1) ◊ (1) ◊ (1 0) ◊")),
+        [
+            TellStatus::Added,
+            TellStatus::Added,
+            TellStatus::Present,
+            TellStatus::Present
+        ]
+    );
+    assert_eq!(db.len(), 2);
+    assert_eq!(db.ask("(1) ◊").len(), 1);
+}
@@ -0,0 +1,176 @@
+// Copyright (c) 2020 by Enrique Pérez Arnaud <enrique at cazalla.net>
+//
+// This file is part of the modus_ponens project.
+// http://www.modus_ponens.net
+//
+// The modus_ponens project is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// The modus_ponens project is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with any part of the modus_ponens project.
+// If not, see <http://www.gnu.org/licenses/>.
+
+extern crate uindex;
+#[macro_use]
+extern crate uindex_derive;
+#[macro_use]
+extern crate pest_derive;
+
+use uindex::kbase::{DBGen, DataBase, TellStatus};
+use uindex::typed::{parse_template, Sentence, TemplatePart};
+
+#[derive(DBGen)]
+#[grammar_inline = r#"
+fact        = { (user | address | town | likes) }
+var         = @{ "X" ~ ASCII_DIGIT* }
+v_number    = @{ ASCII_DIGIT+ }
+number      = _{ var | v_number }
+v_name      = @{ ASCII_ALPHA+ ~ ASCII_DIGIT* }
+name        = _{ var | v_name }
+user        = { "U" ~ name ~ name ~ name }
+address     = { "A" ~ name ~ name ~ number ~ name }
+town        = { "T" ~ name ~ number ~ name }
+likes       = { name ~ "likes" ~ name }
+WHITESPACE  = _{ " " | "\t" | NEWLINE }
+"#]
+pub struct Tables;
+
+#[derive(Sentence, Debug, Clone, PartialEq)]
+#[production = "user"]
+#[sentence = "U {given_name} {surname} {userid}"]
+pub struct User {
+    given_name: String,
+    surname: String,
+    userid: String,
+}
+
+#[derive(Sentence, Debug, Clone, PartialEq)]
+pub enum Place {
+    #[production = "address"]
+    #[sentence = "A {0} {1} {2} {3}"]
+    Address(String, String, u32, String),
+    #[production = "town"]
+    #[sentence = "T {city} {population} {country}"]
+    Town {
+        city: String,
+        population: u64,
+        country: String,
+    },
+}
+
+#[derive(Sentence, Debug, Clone, PartialEq)]
+#[production = "likes"]
+#[sentence = "{who} likes {what}"]
+pub struct Likes {
+    who: String,
+    what: String,
+}
+
+fn user(given_name: &str, surname: &str, userid: &str) -> User {
+    User {
+        given_name: given_name.into(),
+        surname: surname.into(),
+        userid: userid.into(),
+    }
+}
+
+#[test]
+fn to_and_from_sentences() {
+    let db = Tables::gen_db();
+    let john = user("john", "smith", "js1");
+    assert_eq!(john.to_sentence(), "U john smith js1");
+    assert_eq!(db.parse_typed("U  john smith\tjs1"), Some(john));
+    assert_eq!(db.parse_typed::<User>("A john smith 3 js1"), None);
+    let town = Place::Town {
+        city: "paris".into(),
+        population: 2_000_000,
+        country: "france".into(),
+    };
+    assert_eq!(town.to_sentence(), "T paris 2000000 france");
+    assert_eq!(db.parse_typed("T paris 2000000 france"), Some(town));
+    assert_eq!(db.parse_typed::<Place>("T paris many france"), None);
+    assert_eq!(
+        db.parse_typed("A js1 main 5 paris"),
+        Some(Place::Address(
+            "js1".into(),
+            "main".into(),
+            5,
+            "paris".into()
+        ))
+    );
+}
+
+#[test]
+fn values_are_read_from_the_parse_tree() {
+    let db = Tables::gen_db();
+    let likes = Likes {
+        who: "dislikes".into(),
+        what: "apples".into(),
+    };
+    assert_eq!(likes.to_sentence(), "dislikes likes apples");
+    assert_eq!(db.parse_typed("dislikes likes apples"), Some(likes));
+    assert_eq!(
+        db.parse_typed("U Ulrich Uwe U2"),
+        Some(user("Ulrich", "Uwe", "U2"))
+    );
+    db.tell("likes likes likes ◊");
+    let likes: Vec<Likes> = db.ask_typed("X1 likes likes ◊");
+    assert_eq!(likes.len(), 1);
+    assert_eq!(likes[0].who, "likes");
+}
+
+#[test]
+fn tell_and_ask_typed() {
+    let db = Tables::gen_db();
+    let john = user("john", "smith", "js1");
+    assert_eq!(db.tell_typed(&john).status, TellStatus::Added);
+    assert_eq!(db.tell_typed(&john).status, TellStatus::Present);
+    db.tell_typed(&user("jane", "smith", "js2"));
+    db.tell_typed(&user("jane", "doe", "jd1"));
+    db.tell_typed(&Place::Address(
+        "js1".into(),
+        "main".into(),
+        5,
+        "paris".into(),
+    ));
+    db.tell("T paris 2000000 france ◊");
+    let mut smiths: Vec<User> = db.ask_typed("U X1 smith X2 ◊");
+    smiths.sort_by(|a, b| a.userid.cmp(&b.userid));
+    assert_eq!(smiths, [john, user("jane", "smith", "js2")]);
+    let places: Vec<Place> = db.ask_typed("U john smith X1 ◊ A X1 X2 X3 X4 ◊");
+    assert_eq!(
+        places,
+        [Place::Address(
+            "js1".into(),
+            "main".into(),
+            5,
+            "paris".into()
+        )]
+    );
+    let towns: Vec<Place> = db.ask_typed("A js1 X1 X2 X3 ◊ T X3 X4 X5 ◊");
+    assert_eq!(towns.len(), 1);
+    assert!(matches!(&towns[0], Place::Address(..)));
+}
+
+#[test]
+fn templates() {
+    assert_eq!(
+        parse_template("U {a} {{b}} {0}"),
+        Ok(vec![
+            TemplatePart::Literal("U ".into()),
+            TemplatePart::Field("a".into()),
+            TemplatePart::Literal(" {b} ".into()),
+            TemplatePart::Field("0".into()),
+        ])
+    );
+    assert!(parse_template("U {a}{b}").is_err());
+    assert!(parse_template("U {}").is_err());
+    assert!(parse_template("U a}").is_err());
+}
@@ -0,0 +1,94 @@
+/*
+ * Copyright (c) 2020 by Enrique Pérez Arnaud <enrique at cazalla.net>
+ *
+ * This file is part of the modus_ponens project.
+ * http://www.modus_ponens.net
+ *
+ * The modus_ponens project is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * The modus_ponens project is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with any part of the modus_ponens project.
+ * If not, see <http://www.gnu.org/licenses/>.
+ */
+
+/*
+ * An example of the C api, that tells a few sentences, asks about them,
+ * gets one back by its id, and forgets it. It exits with 0 if all went
+ * as expected.
+ */
+
+#include <inttypes.h>
+#include <stdio.h>
+
+#include "uindex.h"
+
+static const char *GRAMMAR =
+    "fact        = { name ~ \"likes\" ~ name }\n"
+    "var         = @{ \"X\" ~ ASCII_DIGIT* }\n"
+    "v_name      = @{ ASCII_ALPHA+ }\n"
+    "name        = _{ var | v_name }\n"
+    "WHITESPACE  = _{ \" \" | \"\\t\" | NEWLINE }\n";
+
+static int fail(const char *what) {
+    const char *error = uindex_last_error();
+    fprintf(stderr, "%s failed: %s\n", what, error ? error : "unexpected result");
+    return 1;
+}
+
+int main(void) {
+    uindex_db *db = NULL;
+    uindex_answers *answers = NULL;
+    uint64_t ids[2];
+    size_t told = 0;
+    char *sentence = NULL;
+    int forgotten = 0;
+    int status = 1;
+
+    if (uindex_db_new(GRAMMAR, &db) != UINDEX_OK) {
+        return fail("uindex_db_new");
+    }
+    if (uindex_tell_ids(db, "susan likes apples ◊ john likes susan ◊", ids, 2, &told) != UINDEX_OK
+        || told != 2) {
+        status = fail("uindex_tell_ids");
+        goto done;
+    }
+    if (uindex_ask(db, "X1 likes X2 ◊", &answers) != UINDEX_OK
+        || uindex_answers_count(answers) != 2) {
+        status = fail("uindex_ask");
+        goto done;
+    }
+    while (uindex_answers_next(answers)) {
+        printf("%s likes %s\n", uindex_answers_get(answers, "X1"),
+               uindex_answers_get(answers, "X2"));
+    }
+    if (uindex_get(db, ids[0], &sentence) != UINDEX_OK || sentence == NULL) {
+        status = fail("uindex_get");
+        goto done;
+    }
+    printf("%" PRIu64 ": %s\n", ids[0], sentence);
+    uindex_string_free(sentence);
+    sentence = NULL;
+    if (uindex_forget(db, ids[0], &forgotten) != UINDEX_OK || !forgotten) {
+        status = fail("uindex_forget");
+        goto done;
+    }
+    if (uindex_get(db, ids[0], &sentence) != UINDEX_OK || sentence != NULL) {
+        status = fail("uindex_get after uindex_forget");
+        goto done;
+    }
+    status = 0;
+
+done:
+    uindex_string_free(sentence);
+    uindex_answers_free(answers);
+    uindex_db_free(db);
+    return status;
+}
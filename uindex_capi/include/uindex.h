@@ -0,0 +1,125 @@
+/*
+ * Copyright (c) 2020 by Enrique Pérez Arnaud <enrique at cazalla.net>
+ *
+ * This file is part of the modus_ponens project.
+ * http://www.modus_ponens.net
+ *
+ * The modus_ponens project is free software: you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation, either version 3 of the License, or
+ * (at your option) any later version.
+ *
+ * The modus_ponens project is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License
+ * along with any part of the modus_ponens project.
+ * If not, see <http://www.gnu.org/licenses/>.
+ */
+
+/*
+ * A C api for uindex, with a db for the sentences of a pest grammar given
+ * at runtime. All strings are NUL terminated UTF-8.
+ *
+ * The functions that can fail return a uindex_status, and leave a message
+ * for the failure, that can be read with uindex_last_error, in the calling
+ * thread. A db, and the answers from it, must only be used from one thread
+ * at a time.
+ */
+
+#ifndef UINDEX_H
+#define UINDEX_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef enum uindex_status {
+    UINDEX_OK = 0,
+    /* A pointer that is required was null. */
+    UINDEX_NULL_POINTER = 1,
+    /* A string was not valid UTF-8. */
+    UINDEX_INVALID_UTF8 = 2,
+    /* The grammar could not be parsed. */
+    UINDEX_INVALID_GRAMMAR = 3,
+    /* A sentence could not be parsed. */
+    UINDEX_INVALID_SENTENCE = 4,
+    /* uindex failed unexpectedly. */
+    UINDEX_PANIC = 5
+} uindex_status;
+
+typedef struct UindexDb uindex_db;
+typedef struct UindexAnswers uindex_answers;
+
+/* Makes a db for the sentences of the grammar, given as pest source,
+ * and stores it in db. It must be freed with uindex_db_free. */
+uindex_status uindex_db_new(const char *grammar, uindex_db **db);
+
+/* Like uindex_db_new, for knowledge in which terminators within
+ * double quoted strings do not end sentences. */
+uindex_status uindex_db_new_with_strings(const char *grammar, uindex_db **db);
+
+/* Frees a db. Does nothing with NULL. */
+void uindex_db_free(uindex_db *db);
+
+/* Tells the knowledge, if all its sentences can be parsed, and stores
+ * how many sentences there were in told, unless it is NULL. */
+uindex_status uindex_tell(uindex_db *db, const char *knowledge, size_t *told);
+
+/* Like uindex_tell, also storing in ids, unless it is NULL, the ids of the
+ * sentences, in order, up to capacity of them. The id of a sentence does not
+ * depend on its formatting, nor on the db it is told to. */
+uindex_status uindex_tell_ids(uindex_db *db, const char *knowledge,
+                              uint64_t *ids, size_t capacity, size_t *told);
+
+/* Stores in sentence the text of the sentence with the id, to be freed
+ * with uindex_string_free, or NULL if it is not in the db. */
+uindex_status uindex_get(uindex_db *db, uint64_t id, char **sentence);
+
+/* Forgets the sentence with the id, and stores in forgotten, unless it
+ * is NULL, 1 if it was in the db, or 0 if it was not. */
+uindex_status uindex_forget(uindex_db *db, uint64_t id, int *forgotten);
+
+/* Frees a string made by uindex. Does nothing with NULL. */
+void uindex_string_free(char *string);
+
+/* Asks the query, and stores the answers in answers. They must be freed
+ * with uindex_answers_free. */
+uindex_status uindex_ask(uindex_db *db, const char *query, uindex_answers **answers);
+
+/* The number of answers. */
+size_t uindex_answers_count(const uindex_answers *answers);
+
+/* Moves the cursor, which starts before the first answer, to the next
+ * answer, returning 1, or 0 if there are no more. */
+int uindex_answers_next(uindex_answers *answers);
+
+/* The number of variables in the answer at the cursor. */
+size_t uindex_answers_len(const uindex_answers *answers);
+
+/* The name and the value of the variable at the index in the answer at
+ * the cursor, or NULL if there is none. They live as long as the answers. */
+const char *uindex_answers_var(const uindex_answers *answers, size_t index);
+const char *uindex_answers_value(const uindex_answers *answers, size_t index);
+
+/* The value of the variable in the answer at the cursor, or NULL if it
+ * is not in the answer. It lives as long as the answers. */
+const char *uindex_answers_get(const uindex_answers *answers, const char *var);
+
+/* Frees answers. Does nothing with NULL. */
+void uindex_answers_free(uindex_answers *answers);
+
+/* The message for the last failure in the calling thread, or NULL if there
+ * was none. It lives until the next failure in the thread. */
+const char *uindex_last_error(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* UINDEX_H */
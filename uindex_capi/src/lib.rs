@@ -0,0 +1,468 @@
+// Copyright (c) 2020 by Enrique Pérez Arnaud <enrique at cazalla.net>
+//
+// This file is part of the modus_ponens project.
+// http://www.modus_ponens.net
+//
+// The modus_ponens project is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// The modus_ponens project is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with any part of the modus_ponens project.
+// If not, see <http://www.gnu.org/licenses/>.
+
+//! A C ABI for uindex, declared in `include/uindex.h`, with a db for the
+//! sentences of a pest grammar given at runtime.
+//!
+//! The functions that can fail return a `UindexStatus`, and leave a message
+//! for the failure, that can be read with `uindex_last_error`, in the calling
+//! thread. A db, and the answers from it, must only be used from one thread
+//! at a time.
+
+use std::cell::RefCell;
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_int};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+use uindex::dynamic::{DynamicDB, KnowledgeSyntax};
+use uindex::kbase::{DataBase, FactId};
+
+/// What happened in a call that can fail.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UindexStatus {
+    Ok = 0,
+    /// A pointer that is required was null.
+    NullPointer = 1,
+    /// A string was not valid UTF-8.
+    InvalidUtf8 = 2,
+    /// The grammar could not be parsed.
+    InvalidGrammar = 3,
+    /// A sentence could not be parsed.
+    InvalidSentence = 4,
+    /// uindex failed unexpectedly.
+    Panic = 5,
+}
+
+/// A db, owned by the caller, to be freed with `uindex_db_free`.
+pub struct UindexDb {
+    // boxed, so that it does not move while the references into it that
+    // it keeps for its sentences are alive
+    db: Box<DynamicDB<'static>>,
+}
+
+impl UindexDb {
+    /// The db, borrowed for as long as the methods of `MPDB` require.
+    /// The results of those methods are owned, so nothing borrowed from
+    /// the db outlives it.
+    fn db(&self) -> &'static DynamicDB<'static> {
+        unsafe { &*(&*self.db as *const DynamicDB<'static>) }
+    }
+}
+
+/// The answers to a query, with a cursor that starts before the first,
+/// owned by the caller, to be freed with `uindex_answers_free`.
+pub struct UindexAnswers {
+    answers: Vec<Vec<(CString, CString)>>,
+    current: Option<usize>,
+}
+
+impl UindexAnswers {
+    fn current(&self) -> &[(CString, CString)] {
+        match self.current {
+            Some(index) if index < self.answers.len() => &self.answers[index],
+            _ => &[],
+        }
+    }
+}
+
+type Failure = (UindexStatus, String);
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Runs the call, recording its failure, or its panic, for `uindex_last_error`.
+fn guard<F: FnOnce() -> Result<(), Failure>>(call: F) -> UindexStatus {
+    let (status, message) = match panic::catch_unwind(AssertUnwindSafe(call)) {
+        Ok(Ok(())) => return UindexStatus::Ok,
+        Ok(Err(failure)) => failure,
+        Err(_) => (UindexStatus::Panic, String::from("uindex panicked")),
+    };
+    let message = c_string(message);
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+    status
+}
+
+/// The text of a C string, that the caller must keep alive while it is used.
+unsafe fn text<'s>(string: *const c_char, name: &str) -> Result<&'s str, Failure> {
+    if string.is_null() {
+        return Err((UindexStatus::NullPointer, format!("{} is null", name)));
+    }
+    CStr::from_ptr(string).to_str().map_err(|_| {
+        (
+            UindexStatus::InvalidUtf8,
+            format!("{} is not valid UTF-8", name),
+        )
+    })
+}
+
+fn c_string(text: String) -> CString {
+    // texts made from C strings have no NULs, but messages might
+    CString::new(text.replace('\0', "")).expect("no NULs")
+}
+
+/// Fails with the first sentence in the knowledge that cannot be parsed, if any.
+fn check(db: &'static DynamicDB<'static>, knowledge: &str) -> Result<(), Failure> {
+    match db.check(knowledge).into_iter().next() {
+        Some(skipped) => Err((
+            UindexStatus::InvalidSentence,
+            format!(
+                "cannot parse the sentence at line {}, column {}:\n{}",
+                skipped.line, skipped.column, skipped.error
+            ),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Makes a db for the sentences of the grammar, given as pest source,
+/// and stores it in `db`.
+///
+/// # Safety
+///
+/// `grammar` must be a C string, and `db` must point to writable memory.
+#[no_mangle]
+pub unsafe extern "C" fn uindex_db_new(
+    grammar: *const c_char,
+    db: *mut *mut UindexDb,
+) -> UindexStatus {
+    new_db(grammar, db, None)
+}
+
+/// Like `uindex_db_new`, for knowledge in which terminators within
+/// double quoted strings do not end sentences.
+///
+/// # Safety
+///
+/// `grammar` must be a C string, and `db` must point to writable memory.
+#[no_mangle]
+pub unsafe extern "C" fn uindex_db_new_with_strings(
+    grammar: *const c_char,
+    db: *mut *mut UindexDb,
+) -> UindexStatus {
+    new_db(grammar, db, Some(KnowledgeSyntax::with_strings()))
+}
+
+unsafe fn new_db(
+    grammar: *const c_char,
+    db: *mut *mut UindexDb,
+    syntax: Option<KnowledgeSyntax>,
+) -> UindexStatus {
+    guard(|| {
+        let grammar = text(grammar, "grammar")?;
+        if db.is_null() {
+            return Err((UindexStatus::NullPointer, String::from("db is null")));
+        }
+        let new = match &syntax {
+            Some(syntax) => DynamicDB::from_grammar_with_syntax(grammar, syntax),
+            None => DynamicDB::from_grammar(grammar),
+        };
+        let new = new.map_err(|errors| {
+            let errors: Vec<String> = errors.iter().map(|error| error.to_string()).collect();
+            (UindexStatus::InvalidGrammar, errors.join("\n"))
+        })?;
+        *db = Box::into_raw(Box::new(UindexDb { db: Box::new(new) }));
+        Ok(())
+    })
+}
+
+/// Frees a db made with `uindex_db_new`. Does nothing with null.
+///
+/// # Safety
+///
+/// `db` must come from `uindex_db_new`, and not have been freed.
+#[no_mangle]
+pub unsafe extern "C" fn uindex_db_free(db: *mut UindexDb) {
+    if !db.is_null() {
+        drop(Box::from_raw(db));
+    }
+}
+
+/// The db behind the pointer, if it is not null.
+unsafe fn live_db(db: *mut UindexDb) -> Result<&'static DynamicDB<'static>, Failure> {
+    db.as_ref()
+        .map(UindexDb::db)
+        .ok_or_else(|| (UindexStatus::NullPointer, String::from("db is null")))
+}
+
+/// Tells the knowledge, if all its sentences can be parsed, and stores
+/// how many sentences there were in `told`, unless it is null.
+///
+/// # Safety
+///
+/// `db` must be a live db, `knowledge` a C string, and `told` null
+/// or pointing to writable memory.
+#[no_mangle]
+pub unsafe extern "C" fn uindex_tell(
+    db: *mut UindexDb,
+    knowledge: *const c_char,
+    told: *mut usize,
+) -> UindexStatus {
+    uindex_tell_ids(db, knowledge, ptr::null_mut(), 0, told)
+}
+
+/// Like `uindex_tell`, also storing in `ids`, unless it is null, the ids
+/// of the sentences, in order, up to `capacity` of them.
+///
+/// # Safety
+///
+/// `db` must be a live db, `knowledge` a C string, `ids` null or pointing
+/// to writable memory for `capacity` ids, and `told` null or pointing
+/// to writable memory.
+#[no_mangle]
+pub unsafe extern "C" fn uindex_tell_ids(
+    db: *mut UindexDb,
+    knowledge: *const c_char,
+    ids: *mut u64,
+    capacity: usize,
+    told: *mut usize,
+) -> UindexStatus {
+    guard(|| {
+        let db = live_db(db)?;
+        let knowledge = text(knowledge, "knowledge")?;
+        check(db, knowledge)?;
+        let sentences = db.tell(knowledge);
+        if !ids.is_null() {
+            for (index, sentence) in sentences.iter().take(capacity).enumerate() {
+                *ids.add(index) = sentence.id.0;
+            }
+        }
+        if !told.is_null() {
+            *told = sentences.len();
+        }
+        Ok(())
+    })
+}
+
+/// Stores in `sentence` the text of the sentence with the id, to be freed
+/// with `uindex_string_free`, or null if it is not in the db.
+///
+/// # Safety
+///
+/// `db` must be a live db, and `sentence` must point to writable memory.
+#[no_mangle]
+pub unsafe extern "C" fn uindex_get(
+    db: *mut UindexDb,
+    id: u64,
+    sentence: *mut *mut c_char,
+) -> UindexStatus {
+    guard(|| {
+        let db = live_db(db)?;
+        if sentence.is_null() {
+            return Err((UindexStatus::NullPointer, String::from("sentence is null")));
+        }
+        *sentence = match db.get(FactId(id)) {
+            Some(text) => c_string(text).into_raw(),
+            None => ptr::null_mut(),
+        };
+        Ok(())
+    })
+}
+
+/// Forgets the sentence with the id, and stores in `forgotten`, unless it
+/// is null, 1 if it was in the db, or 0 if it was not.
+///
+/// # Safety
+///
+/// `db` must be a live db, and `forgotten` null or pointing to writable memory.
+#[no_mangle]
+pub unsafe extern "C" fn uindex_forget(
+    db: *mut UindexDb,
+    id: u64,
+    forgotten: *mut c_int,
+) -> UindexStatus {
+    guard(|| {
+        let db = live_db(db)?;
+        let was = db.forget(FactId(id));
+        if !forgotten.is_null() {
+            *forgotten = was as c_int;
+        }
+        Ok(())
+    })
+}
+
+/// Frees a string made by uindex. Does nothing with null.
+///
+/// # Safety
+///
+/// `string` must come from `uindex_get`, and not have been freed.
+#[no_mangle]
+pub unsafe extern "C" fn uindex_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
+
+/// Asks the query, and stores the answers in `answers`.
+///
+/// # Safety
+///
+/// `db` must be a live db, `query` a C string, and `answers` must point
+/// to writable memory.
+#[no_mangle]
+pub unsafe extern "C" fn uindex_ask(
+    db: *mut UindexDb,
+    query: *const c_char,
+    answers: *mut *mut UindexAnswers,
+) -> UindexStatus {
+    guard(|| {
+        let db = live_db(db)?;
+        let query = text(query, "query")?;
+        if answers.is_null() {
+            return Err((UindexStatus::NullPointer, String::from("answers is null")));
+        }
+        check(db, query)?;
+        let found = db
+            .ask_bindings(query)
+            .into_iter()
+            .map(|answer| {
+                answer
+                    .into_iter()
+                    .map(|binding| (c_string(binding.var), c_string(binding.value)))
+                    .collect()
+            })
+            .collect();
+        *answers = Box::into_raw(Box::new(UindexAnswers {
+            answers: found,
+            current: None,
+        }));
+        Ok(())
+    })
+}
+
+/// The number of answers.
+///
+/// # Safety
+///
+/// `answers` must come from `uindex_ask`, and not have been freed.
+#[no_mangle]
+pub unsafe extern "C" fn uindex_answers_count(answers: *const UindexAnswers) -> usize {
+    answers.as_ref().map_or(0, |answers| answers.answers.len())
+}
+
+/// Moves the cursor to the next answer, returning 1, or 0 if there are no more.
+///
+/// # Safety
+///
+/// `answers` must come from `uindex_ask`, and not have been freed.
+#[no_mangle]
+pub unsafe extern "C" fn uindex_answers_next(answers: *mut UindexAnswers) -> c_int {
+    let answers = match answers.as_mut() {
+        Some(answers) => answers,
+        None => return 0,
+    };
+    let next = answers.current.map_or(0, |index| index + 1);
+    answers.current = Some(next.min(answers.answers.len()));
+    (next < answers.answers.len()) as c_int
+}
+
+/// The number of variables in the answer at the cursor.
+///
+/// # Safety
+///
+/// `answers` must come from `uindex_ask`, and not have been freed.
+#[no_mangle]
+pub unsafe extern "C" fn uindex_answers_len(answers: *const UindexAnswers) -> usize {
+    answers
+        .as_ref()
+        .map_or(0, |answers| answers.current().len())
+}
+
+/// The name of the variable at the index in the answer at the cursor,
+/// or null if there is none. It lives as long as the answers.
+///
+/// # Safety
+///
+/// `answers` must come from `uindex_ask`, and not have been freed.
+#[no_mangle]
+pub unsafe extern "C" fn uindex_answers_var(
+    answers: *const UindexAnswers,
+    index: usize,
+) -> *const c_char {
+    answers
+        .as_ref()
+        .and_then(|answers| answers.current().get(index))
+        .map_or(ptr::null(), |(var, _)| var.as_ptr())
+}
+
+/// The value of the variable at the index in the answer at the cursor,
+/// or null if there is none. It lives as long as the answers.
+///
+/// # Safety
+///
+/// `answers` must come from `uindex_ask`, and not have been freed.
+#[no_mangle]
+pub unsafe extern "C" fn uindex_answers_value(
+    answers: *const UindexAnswers,
+    index: usize,
+) -> *const c_char {
+    answers
+        .as_ref()
+        .and_then(|answers| answers.current().get(index))
+        .map_or(ptr::null(), |(_, value)| value.as_ptr())
+}
+
+/// The value of the variable in the answer at the cursor, or null
+/// if it is not in the answer. It lives as long as the answers.
+///
+/// # Safety
+///
+/// `answers` must come from `uindex_ask`, and not have been freed,
+/// and `var` must be a C string.
+#[no_mangle]
+pub unsafe extern "C" fn uindex_answers_get(
+    answers: *const UindexAnswers,
+    var: *const c_char,
+) -> *const c_char {
+    let answers = match answers.as_ref() {
+        Some(answers) if !var.is_null() => answers,
+        _ => return ptr::null(),
+    };
+    let var = CStr::from_ptr(var);
+    answers
+        .current()
+        .iter()
+        .find(|(name, _)| name.as_c_str() == var)
+        .map_or(ptr::null(), |(_, value)| value.as_ptr())
+}
+
+/// Frees answers made with `uindex_ask`. Does nothing with null.
+///
+/// # Safety
+///
+/// `answers` must come from `uindex_ask`, and not have been freed.
+#[no_mangle]
+pub unsafe extern "C" fn uindex_answers_free(answers: *mut UindexAnswers) {
+    if !answers.is_null() {
+        drop(Box::from_raw(answers));
+    }
+}
+
+/// The message for the last failure in the calling thread, or null if there
+/// was none. It lives until the next failure in the thread.
+#[no_mangle]
+pub extern "C" fn uindex_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(ptr::null(), |message| message.as_ptr())
+    })
+}
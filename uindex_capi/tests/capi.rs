@@ -0,0 +1,143 @@
+// Copyright (c) 2020 by Enrique Pérez Arnaud <enrique at cazalla.net>
+//
+// This file is part of the modus_ponens project.
+// http://www.modus_ponens.net
+//
+// The modus_ponens project is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// The modus_ponens project is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with any part of the modus_ponens project.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_int};
+use std::ptr;
+
+use uindex_capi::*;
+
+const GRAMMAR: &str = r#"
+fact        = { name ~ "likes" ~ name }
+var         = @{ "X" ~ ASCII_DIGIT* }
+v_name      = @{ ASCII_ALPHA+ }
+name        = _{ var | v_name }
+WHITESPACE  = _{ " " | "\t" | NEWLINE }
+"#;
+
+fn c(text: &str) -> CString {
+    CString::new(text).unwrap()
+}
+
+fn new_db() -> *mut UindexDb {
+    let mut db = ptr::null_mut();
+    let status = unsafe { uindex_db_new(c(GRAMMAR).as_ptr(), &mut db) };
+    assert_eq!(status, UindexStatus::Ok);
+    db
+}
+
+fn get(db: *mut UindexDb, id: u64) -> Option<String> {
+    let mut sentence: *mut c_char = ptr::null_mut();
+    unsafe {
+        assert_eq!(uindex_get(db, id, &mut sentence), UindexStatus::Ok);
+        if sentence.is_null() {
+            return None;
+        }
+        let text = CStr::from_ptr(sentence).to_str().unwrap().to_string();
+        uindex_string_free(sentence);
+        Some(text)
+    }
+}
+
+#[test]
+fn tells_with_ids_gets_and_forgets() {
+    let db = new_db();
+    let mut ids = [0u64; 1];
+    let mut told = 0;
+    let knowledge = c("susan likes apples ◊ john likes  susan ◊");
+    unsafe {
+        let status = uindex_tell_ids(db, knowledge.as_ptr(), ids.as_mut_ptr(), 1, &mut told);
+        assert_eq!(status, UindexStatus::Ok);
+    }
+    assert_eq!(told, 2);
+    assert_eq!(get(db, ids[0]).as_deref(), Some("susan likes apples"));
+    let mut forgotten: c_int = -1;
+    unsafe {
+        assert_eq!(uindex_forget(db, ids[0], &mut forgotten), UindexStatus::Ok);
+        assert_eq!(forgotten, 1);
+        assert_eq!(uindex_forget(db, ids[0], &mut forgotten), UindexStatus::Ok);
+        assert_eq!(forgotten, 0);
+    }
+    assert_eq!(get(db, ids[0]), None);
+    unsafe { uindex_db_free(db) };
+}
+
+#[test]
+fn ids_do_not_depend_on_the_db() {
+    let (one, other) = (new_db(), new_db());
+    let (mut first, mut second) = (0u64, 0u64);
+    unsafe {
+        let knowledge = c("susan likes apples ◊");
+        uindex_tell_ids(one, knowledge.as_ptr(), &mut first, 1, ptr::null_mut());
+        let knowledge = c("susan   likes apples ◊");
+        uindex_tell_ids(other, knowledge.as_ptr(), &mut second, 1, ptr::null_mut());
+        uindex_db_free(one);
+        uindex_db_free(other);
+    }
+    assert_eq!(first, second);
+}
+
+#[test]
+fn null_pointers_are_errors() {
+    let mut forgotten: c_int = 0;
+    unsafe {
+        let status = uindex_forget(ptr::null_mut(), 1, &mut forgotten);
+        assert_eq!(status, UindexStatus::NullPointer);
+        let db = new_db();
+        assert_eq!(
+            uindex_get(db, 1, ptr::null_mut()),
+            UindexStatus::NullPointer
+        );
+        assert!(!uindex_last_error().is_null());
+        uindex_db_free(db);
+    }
+}
+
+#[test]
+fn grammars_without_a_fact_are_invalid() {
+    let mut db = ptr::null_mut();
+    let grammar = c("sentence = { var }\nvar = @{ \"X\" ~ ASCII_DIGIT* }");
+    let status = unsafe { uindex_db_new(grammar.as_ptr(), &mut db) };
+    assert_eq!(status, UindexStatus::InvalidGrammar);
+    assert!(db.is_null());
+}
+
+#[test]
+fn terminators_within_strings() {
+    let grammar = c(r#"
+fact        = { name ~ "says" ~ quote }
+var         = @{ "X" ~ ASCII_DIGIT* }
+v_name      = @{ ASCII_ALPHA+ }
+name        = _{ var | v_name }
+v_quote     = @{ "\"" ~ (!"\"" ~ ANY)* ~ "\"" }
+quote       = _{ var | v_quote }
+WHITESPACE  = _{ " " | "\t" | NEWLINE }
+"#);
+    let mut db = ptr::null_mut();
+    let mut told = 0;
+    let knowledge = c(r#"susan says "see you ◊ soon" ◊ john says "bye" ◊"#);
+    unsafe {
+        let status = uindex_db_new_with_strings(grammar.as_ptr(), &mut db);
+        assert_eq!(status, UindexStatus::Ok);
+        let status = uindex_tell_ids(db, knowledge.as_ptr(), ptr::null_mut(), 0, &mut told);
+        assert_eq!(status, UindexStatus::Ok);
+        uindex_db_free(db);
+    }
+    assert_eq!(told, 2);
+}
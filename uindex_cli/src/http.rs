@@ -0,0 +1,356 @@
+// Copyright (c) 2020 by Enrique Pérez Arnaud <enrique at cazalla.net>
+//
+// This file is part of the modus_ponens project.
+// http://www.modus_ponens.net
+//
+// The modus_ponens project is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// The modus_ponens project is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with any part of the modus_ponens project.
+// If not, see <http://www.gnu.org/licenses/>.
+
+//! A JSON api for a db over HTTP. Requests are served one at a time, each
+//! on its own connection, since the db cannot be shared between threads.
+//! So that a client that stalls does not keep the rest waiting, a connection
+//! is dropped when nothing can be read from or written to it for `TIMEOUT`.
+//! The request line and headers can take at most `MAX_HEAD` bytes, and a request
+//! that makes the db panic gets a 500 response, without stopping the server.
+//!
+//! - `POST /tell` with `{"knowledge": "..."}` tells the sentences, if they can all be
+//!   parsed, and returns `{"told": [{"id": "...", "status": "added"}, ...]}`.
+//! - `POST /ask` with `{"query": "..."}` returns `{"answers": [...]}`, each answer
+//!   mapping the variables in the query to their values.
+//! - `POST /forget` with `{"id": "..."}` returns `{"forgotten": true}`, or `false`
+//!   if there was no sentence with the id in the db.
+//! - `POST /load` with knowledge as the body tells it as it is read, skipping the
+//!   sentences that cannot be parsed, and returns the import report.
+//! - `GET /stats` returns the number of sentences in the db, and by production.
+//!
+//! Ids are strings, since they do not fit in the numbers of many JSON parsers.
+//! Errors are returned with a 4xx status and `{"error": "...", "skipped": [...]}`,
+//! with the sentences that could not be parsed, if any.
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::panic::{self, AssertUnwindSafe};
+use std::time::Duration;
+
+use log::{info, warn};
+use serde_json::{json, Value};
+
+use uindex::dynamic::DynamicDB;
+use uindex::kbase::{DataBase, FactId, Told};
+use uindex::stream::Skipped;
+
+/// How long a connection can stall before it is dropped.
+pub const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How many bytes the request line and headers of a request can take.
+pub const MAX_HEAD: u64 = 16 * 1024;
+
+/// Serves the db on the listener, until it fails.
+pub fn serve<'a>(db: &'a DynamicDB<'a>, listener: TcpListener) -> io::Result<()> {
+    serve_with_timeout(db, listener, TIMEOUT)
+}
+
+fn serve_with_timeout<'a>(
+    db: &'a DynamicDB<'a>,
+    listener: TcpListener,
+    timeout: Duration,
+) -> io::Result<()> {
+    for stream in listener.incoming() {
+        let stream = stream?;
+        stream.set_read_timeout(Some(timeout))?;
+        stream.set_write_timeout(Some(timeout))?;
+        if let Err(error) = handle(db, &stream) {
+            warn!("Failed to serve {:?}: {}", stream.peer_addr(), error);
+        }
+    }
+    Ok(())
+}
+
+struct Request {
+    method: String,
+    path: String,
+    content_length: Option<u64>,
+}
+
+fn handle<'a>(db: &'a DynamicDB<'a>, stream: &TcpStream) -> io::Result<()> {
+    let mut reader = BufReader::new(stream);
+    let mut head = reader.by_ref().take(MAX_HEAD);
+    let request = read_head(&mut head)?;
+    let too_large = head.limit() == 0;
+    let (status, body) = match request {
+        Some(request) => {
+            info!("{} {}", request.method, request.path);
+            let body = reader.take(request.content_length.unwrap_or(0));
+            guarded(|| respond(db, &request, BufReader::new(body)))
+        }
+        None if too_large => error(431, "the request line and headers are too large"),
+        None => error(400, "malformed request"),
+    };
+    let body = body.to_string();
+    let mut writer = io::BufWriter::new(stream);
+    write!(
+        writer,
+        "HTTP/1.1 {} {}\r\n\
+         Content-Type: application/json\r\n\
+         Content-Length: {}\r\n\
+         Connection: close\r\n\r\n{}",
+        status,
+        reason(status),
+        body.len(),
+        body
+    )?;
+    writer.flush()
+}
+
+/// The response of the endpoint, or a 500 response if it panics.
+fn guarded<F: FnOnce() -> (u16, Value)>(endpoint: F) -> (u16, Value) {
+    panic::catch_unwind(AssertUnwindSafe(endpoint)).unwrap_or_else(|_| {
+        warn!("The db panicked serving a request");
+        error(500, "internal error")
+    })
+}
+
+/// Reads the request line and headers, or `None` if they are malformed.
+fn read_head<R: BufRead>(reader: &mut R) -> io::Result<Option<Request>> {
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let mut parts = line.split_whitespace();
+    let (method, path) = match (parts.next(), parts.next()) {
+        (Some(method), Some(path)) => (method.to_string(), path.to_string()),
+        _ => return Ok(None),
+    };
+    let mut content_length = None;
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some(colon) = header.find(':') {
+            if header[..colon].eq_ignore_ascii_case("content-length") {
+                match header[colon + 1..].trim().parse() {
+                    Ok(length) => content_length = Some(length),
+                    Err(_) => return Ok(None),
+                }
+            }
+        }
+    }
+    Ok(Some(Request {
+        method,
+        path,
+        content_length,
+    }))
+}
+
+fn respond<'a, R: BufRead>(db: &'a DynamicDB<'a>, request: &Request, body: R) -> (u16, Value) {
+    let endpoint = (request.method.as_str(), request.path.as_str());
+    if let ("POST", _) = endpoint {
+        if request.content_length.is_none() {
+            return error(411, "the body must have a Content-Length");
+        }
+    }
+    match endpoint {
+        ("POST", "/tell") => with_field(body, "knowledge", |knowledge| tell(db, knowledge)),
+        ("POST", "/ask") => with_field(body, "query", |query| ask(db, query)),
+        ("POST", "/forget") => with_field(body, "id", |id| forget(db, id)),
+        ("POST", "/load") => match db.import_reader(body) {
+            Ok(report) => (200, json!(report)),
+            Err(failure) => error(400, &failure.to_string()),
+        },
+        ("GET", "/stats") => (200, json!(db.stats())),
+        (_, "/tell") | (_, "/ask") | (_, "/forget") | (_, "/load") | (_, "/stats") => {
+            error(405, "method not allowed")
+        }
+        _ => error(404, "not found"),
+    }
+}
+
+/// Calls the endpoint with the string in the field of the JSON object in the body.
+fn with_field<R, F>(body: R, field: &str, endpoint: F) -> (u16, Value)
+where
+    R: Read,
+    F: FnOnce(&str) -> (u16, Value),
+{
+    let object: Value = match serde_json::from_reader(body) {
+        Ok(object) => object,
+        Err(failure) => return error(400, &format!("invalid JSON: {}", failure)),
+    };
+    match object.get(field).and_then(Value::as_str) {
+        Some(value) => endpoint(value),
+        None => error(400, &format!("expected a string in the `{}` field", field)),
+    }
+}
+
+fn tell<'a>(db: &'a DynamicDB<'a>, knowledge: &str) -> (u16, Value) {
+    let skipped = db.check(knowledge);
+    if !skipped.is_empty() {
+        return unparsable(skipped);
+    }
+    let told: Vec<Value> = db
+        .tell(knowledge)
+        .into_iter()
+        .map(|Told { id, status }| {
+            let status = format!("{:?}", status).to_lowercase();
+            json!({ "id": id.0.to_string(), "status": status })
+        })
+        .collect();
+    (200, json!({ "told": told }))
+}
+
+fn ask<'a>(db: &'a DynamicDB<'a>, query: &str) -> (u16, Value) {
+    let skipped = db.check(query);
+    if !skipped.is_empty() {
+        return unparsable(skipped);
+    }
+    (200, json!({ "answers": db.ask_bindings(query) }))
+}
+
+fn forget<'a>(db: &'a DynamicDB<'a>, id: &str) -> (u16, Value) {
+    match id.parse() {
+        Ok(id) => (200, json!({ "forgotten": db.forget(FactId(id)) })),
+        Err(_) => error(400, "ids are strings of digits"),
+    }
+}
+
+fn unparsable(skipped: Vec<Skipped>) -> (u16, Value) {
+    let message = "some sentences cannot be parsed";
+    (400, json!({ "error": message, "skipped": skipped }))
+}
+
+fn error(status: u16, message: &str) -> (u16, Value) {
+    (status, json!({ "error": message }))
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        411 => "Length Required",
+        431 => "Request Header Fields Too Large",
+        500 => "Internal Server Error",
+        _ => "Error",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::net::SocketAddr;
+    use std::thread;
+    use std::time::Instant;
+
+    const GRAMMAR: &str = r#"
+fact        = { name ~ "likes" ~ name }
+var         = @{ "X" ~ ASCII_DIGIT* }
+v_name      = @{ ASCII_ALPHA+ }
+name        = _{ var | v_name }
+WHITESPACE  = _{ " " | "\t" | NEWLINE }
+"#;
+
+    /// Serves a new db on a free port, and returns its address.
+    fn start(timeout: Duration) -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            let db: &'static DynamicDB =
+                Box::leak(Box::new(DynamicDB::from_grammar(GRAMMAR).unwrap()));
+            serve_with_timeout(db, listener, timeout)
+        });
+        address
+    }
+
+    fn request(address: SocketAddr, method: &str, path: &str, body: &str) -> (u16, Value) {
+        let mut stream = TcpStream::connect(address).unwrap();
+        write!(
+            stream,
+            "{} {} HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{}",
+            method,
+            path,
+            body.len(),
+            body
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let status = response[9..12].parse().unwrap();
+        let body = &response[response.find("\r\n\r\n").unwrap() + 4..];
+        (status, serde_json::from_str(body).unwrap())
+    }
+
+    #[test]
+    fn serves_the_db() {
+        let address = start(TIMEOUT);
+        let knowledge = json!({ "knowledge": "susan likes oranges ◊ susan likes apples ◊" });
+        let (status, told) = request(address, "POST", "/tell", &knowledge.to_string());
+        assert_eq!(status, 200);
+        assert_eq!(told["told"][0]["status"], "added");
+        let query = json!({ "query": "susan likes X1 ◊" }).to_string();
+        let (_, answers) = request(address, "POST", "/ask", &query);
+        assert_eq!(answers["answers"].as_array().unwrap().len(), 2);
+        let id = json!({ "id": told["told"][0]["id"] }).to_string();
+        let (_, forgotten) = request(address, "POST", "/forget", &id);
+        assert_eq!(forgotten["forgotten"], true);
+        let (_, stats) = request(address, "GET", "/stats", "");
+        assert_eq!(stats["sentences"], 1);
+        let (status, _) = request(address, "POST", "/load", "susan oranges ◊");
+        assert_eq!(status, 200);
+        let (status, error) = request(address, "POST", "/tell", r#"{"knowledge": "susan ◊"}"#);
+        assert_eq!(status, 400);
+        assert_eq!(error["skipped"].as_array().unwrap().len(), 1);
+        assert_eq!(request(address, "GET", "/tell", "").0, 405);
+        assert_eq!(request(address, "GET", "/nowhere", "").0, 404);
+    }
+
+    #[test]
+    fn stalled_clients_are_dropped() {
+        let address = start(Duration::from_millis(200));
+        let started = Instant::now();
+        let _stalled = TcpStream::connect(address).unwrap();
+        let (status, _) = request(address, "GET", "/stats", "");
+        assert_eq!(status, 200);
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn large_heads_are_refused() {
+        let address = start(TIMEOUT);
+        let mut stream = TcpStream::connect(address).unwrap();
+        let head = format!(
+            "GET /stats HTTP/1.1\r\nX-Padding: {}",
+            "x".repeat(MAX_HEAD as usize)
+        );
+        stream
+            .write_all(&head.as_bytes()[..MAX_HEAD as usize])
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 431 "), "{}", response);
+        let (status, _) = request(address, "GET", "/stats", "");
+        assert_eq!(status, 200);
+    }
+
+    #[test]
+    fn panics_are_internal_errors() {
+        let (status, body) = guarded(|| panic!("the db panicked"));
+        assert_eq!(status, 500);
+        assert_eq!(body["error"], "internal error");
+        assert_eq!(guarded(|| error(404, "no such endpoint")).0, 404);
+    }
+}
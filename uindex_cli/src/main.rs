@@ -0,0 +1,108 @@
+// Copyright (c) 2020 by Enrique Pérez Arnaud <enrique at cazalla.net>
+//
+// This file is part of the modus_ponens project.
+// http://www.modus_ponens.net
+//
+// The modus_ponens project is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// The modus_ponens project is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with any part of the modus_ponens project.
+// If not, see <http://www.gnu.org/licenses/>.
+
+extern crate uindex;
+
+use std::fs;
+use std::io;
+use std::net::{SocketAddr, TcpListener};
+use std::path::PathBuf;
+use std::process;
+
+use structopt::StructOpt;
+
+use uindex::dynamic::{DynamicDB, KnowledgeSyntax};
+
+mod http;
+mod repl;
+mod resp;
+
+#[derive(Debug, StructOpt)]
+#[structopt(name = "uindex", about = "A uindex db for any pest grammar.")]
+struct Opt {
+    /// pest grammar for the sentences in the db
+    #[structopt(parse(from_os_str))]
+    grammar: PathBuf,
+
+    /// files with knowledge to load into the db, e.g. saved with :save
+    #[structopt(parse(from_os_str))]
+    files: Vec<PathBuf>,
+
+    /// allow double quoted strings in knowledge, within which terminators do not end sentences
+    #[structopt(long)]
+    strings: bool,
+
+    /// serve the db over HTTP at this address, instead of interactively
+    #[structopt(long)]
+    http: Option<SocketAddr>,
+
+    /// serve the db with the Redis protocol at this address, instead of interactively
+    #[structopt(long, conflicts_with = "http")]
+    resp: Option<SocketAddr>,
+}
+
+fn main() {
+    let opt = Opt::from_args();
+    env_logger::init();
+    let grammar = fs::read_to_string(&opt.grammar).unwrap_or_else(|error| {
+        eprintln!("cannot read {}: {}", opt.grammar.display(), error);
+        process::exit(1);
+    });
+    let db = if opt.strings {
+        DynamicDB::from_grammar_with_syntax(&grammar, &KnowledgeSyntax::with_strings())
+    } else {
+        DynamicDB::from_grammar(&grammar)
+    };
+    let db = db.unwrap_or_else(|errors| {
+        let path = opt.grammar.to_string_lossy();
+        for error in errors {
+            eprintln!("{}", error.with_path(&path));
+        }
+        process::exit(1);
+    });
+    let stdout = io::stdout();
+    let mut repl = repl::Repl::new(&db, stdout.lock());
+    for file in &opt.files {
+        repl.load(file).expect("write to stdout");
+    }
+    if let Some(address) = opt.http {
+        let listener = TcpListener::bind(address).unwrap_or_else(|error| {
+            eprintln!("cannot listen at {}: {}", address, error);
+            process::exit(1);
+        });
+        println!("serving at http://{}", address);
+        if let Err(error) = http::serve(&db, listener) {
+            eprintln!("{}", error);
+            process::exit(1);
+        }
+    } else if let Some(address) = opt.resp {
+        let listener = TcpListener::bind(address).unwrap_or_else(|error| {
+            eprintln!("cannot listen at {}: {}", address, error);
+            process::exit(1);
+        });
+        println!("serving the Redis protocol at {}", address);
+        if let Err(error) = resp::serve(&db, listener) {
+            eprintln!("{}", error);
+            process::exit(1);
+        }
+    } else {
+        let stdin = io::stdin();
+        repl.run(stdin.lock()).expect("write to stdout");
+    }
+}
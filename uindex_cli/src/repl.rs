@@ -0,0 +1,303 @@
+// Copyright (c) 2020 by Enrique Pérez Arnaud <enrique at cazalla.net>
+//
+// This file is part of the modus_ponens project.
+// http://www.modus_ponens.net
+//
+// The modus_ponens project is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// The modus_ponens project is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with any part of the modus_ponens project.
+// If not, see <http://www.gnu.org/licenses/>.
+
+use std::fs::File;
+use std::io::{self, BufRead, Write};
+use std::mem;
+use std::path::Path;
+
+use uindex::bindings::Tree;
+use uindex::dynamic::DynamicDB;
+use uindex::kbase::DataBase;
+use uindex::sentence::SentenceParser;
+use uindex::stream::{ImportReport, Skipped};
+
+const HELP: &str = "\
+Sentences are told to the db, and queries, starting with `?`, asked,
+once they end with a terminator (`◊` or `<>`). Commands:
+  :load FILE      tell the knowledge in the file, skipping invalid sentences
+  :save FILE      write all the sentences in the db to the file
+  :stats          count the sentences in the db
+  :explain QUERY  show how the sentences in the query are parsed and matched
+  :help           show this help
+  :quit           leave";
+
+/// Reads sentences, queries and commands for a db, and writes what they do.
+pub struct Repl<'a, W> {
+    db: &'a DynamicDB<'a>,
+    out: W,
+}
+
+impl<'a, W: Write> Repl<'a, W> {
+    pub fn new(db: &'a DynamicDB<'a>, out: W) -> Repl<'a, W> {
+        Repl { db, out }
+    }
+
+    /// Reads lines until the input ends or `:quit`.
+    pub fn run<R: BufRead>(&mut self, input: R) -> io::Result<()> {
+        let mut pending = String::new();
+        self.prompt(&pending)?;
+        for line in input.lines() {
+            let line = line?;
+            if pending.trim().is_empty() && line.trim_start().starts_with(':') {
+                pending.clear();
+                if !self.command(line.trim())? {
+                    return Ok(());
+                }
+            } else {
+                pending.push_str(&line);
+                pending.push('\n');
+                if pending.trim().is_empty() {
+                    pending.clear();
+                } else if self.is_complete(&pending) {
+                    let knowledge = mem::take(&mut pending);
+                    self.knowledge(knowledge.trim())?;
+                }
+            }
+            self.prompt(&pending)?;
+        }
+        writeln!(self.out)
+    }
+
+    /// Tells the knowledge in the file.
+    pub fn load(&mut self, path: &Path) -> io::Result<()> {
+        match self.db.import_file(path) {
+            Ok(report) => self.report(&report),
+            Err(error) => writeln!(self.out, "cannot load {}: {}", path.display(), error),
+        }
+    }
+
+    fn prompt(&mut self, pending: &str) -> io::Result<()> {
+        let prompt = if pending.is_empty() {
+            "uindex> "
+        } else {
+            "   ...> "
+        };
+        write!(self.out, "{}", prompt)?;
+        self.out.flush()
+    }
+
+    fn is_complete(&self, knowledge: &str) -> bool {
+        let knowledge = knowledge.trim_end();
+        let terminators = self.db.parser().terminators();
+        terminators.iter().any(|t| knowledge.ends_with(t))
+    }
+
+    /// Runs the command, and returns whether to go on.
+    fn command(&mut self, line: &str) -> io::Result<bool> {
+        let (command, argument) = match line.find(char::is_whitespace) {
+            Some(end) => (&line[..end], line[end..].trim()),
+            None => (line, ""),
+        };
+        match (command, argument) {
+            (":quit", _) | (":q", _) => return Ok(false),
+            (":help", _) => writeln!(self.out, "{}", HELP)?,
+            (":load", path) if !path.is_empty() => self.load(Path::new(path))?,
+            (":save", path) if !path.is_empty() => self.save(Path::new(path))?,
+            (":stats", _) => self.stats()?,
+            (":explain", query) if !query.is_empty() => self.explain(query)?,
+            (":load", _) | (":save", _) => writeln!(self.out, "{} needs a file", command)?,
+            (":explain", _) => writeln!(self.out, ":explain needs a query")?,
+            _ => writeln!(self.out, "unknown command {}, try :help", command)?,
+        }
+        Ok(true)
+    }
+
+    fn knowledge(&mut self, knowledge: &str) -> io::Result<()> {
+        if let Some(query) = knowledge.strip_prefix('?') {
+            self.ask(query)
+        } else {
+            let report = self.db.import(knowledge);
+            self.report(&report)
+        }
+    }
+
+    fn ask(&mut self, query: &str) -> io::Result<()> {
+        if !self.check(query)? {
+            return Ok(());
+        }
+        let answers = self.db.ask_bindings(query);
+        if answers.is_empty() {
+            return writeln!(self.out, "no");
+        }
+        for answer in answers {
+            if answer.is_empty() {
+                writeln!(self.out, "yes")?;
+            } else {
+                let values: Vec<String> = answer
+                    .iter()
+                    .map(|binding| format!("{} = {}", binding.var, binding.value))
+                    .collect();
+                writeln!(self.out, "{}", values.join(", "))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn explain(&mut self, query: &str) -> io::Result<()> {
+        if !self.check(query)? {
+            return Ok(());
+        }
+        let terminator = self.db.parser().terminator();
+        for tree in self.db.trees(query) {
+            let sentence = format!("{} {}", tree.text.trim(), terminator);
+            let matches = self.db.ask(&sentence).len();
+            writeln!(self.out, "{}  ({} in the db)", sentence, matches)?;
+            self.tree(&tree, 1)?;
+        }
+        let answers = self.db.ask(query).len();
+        writeln!(self.out, "{} answers", answers)
+    }
+
+    fn tree(&mut self, tree: &Tree, depth: usize) -> io::Result<()> {
+        let indent = "  ".repeat(depth);
+        if !tree.children.is_empty() {
+            writeln!(self.out, "{}{}", indent, tree.production)?;
+            for child in &tree.children {
+                self.tree(child, depth + 1)?;
+            }
+            return Ok(());
+        }
+        let var_names = self.db.parser().var_names();
+        let kind = if var_names.is_var(&tree.production) {
+            "  (variable)"
+        } else if var_names.in_var_range(&tree.production) {
+            "  (can be a variable)"
+        } else {
+            ""
+        };
+        writeln!(
+            self.out,
+            "{}{} {}{}",
+            indent, tree.production, tree.text, kind
+        )
+    }
+
+    fn save(&mut self, path: &Path) -> io::Result<()> {
+        let saved = File::create(path).and_then(|file| self.db.dump(io::BufWriter::new(file)));
+        match saved {
+            Ok(()) => writeln!(self.out, "saved to {}", path.display()),
+            Err(error) => writeln!(self.out, "cannot save {}: {}", path.display(), error),
+        }
+    }
+
+    fn stats(&mut self) -> io::Result<()> {
+        let stats = self.db.stats();
+        writeln!(self.out, "{} sentences", stats.sentences)?;
+        for (production, count) in &stats.productions {
+            writeln!(self.out, "  {}: {}", production, count)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the sentences in the knowledge that cannot be parsed,
+    /// and returns whether there are none.
+    fn check(&mut self, knowledge: &str) -> io::Result<bool> {
+        let skipped = self.db.check(knowledge);
+        for skipped in &skipped {
+            self.skipped(skipped)?;
+        }
+        Ok(skipped.is_empty())
+    }
+
+    fn report(&mut self, report: &ImportReport) -> io::Result<()> {
+        for skipped in &report.skipped {
+            self.skipped(skipped)?;
+        }
+        writeln!(
+            self.out,
+            "{} added, {} present, {} replaced, {} skipped",
+            report.added,
+            report.present,
+            report.replaced,
+            report.skipped.len()
+        )
+    }
+
+    fn skipped(&mut self, skipped: &Skipped) -> io::Result<()> {
+        writeln!(self.out, "{}", skipped)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GRAMMAR: &str = r#"
+fact        = { name ~ "likes" ~ name }
+var         = @{ "X" ~ ASCII_DIGIT* }
+v_name      = @{ ASCII_ALPHA+ }
+name        = _{ var | v_name }
+WHITESPACE  = _{ " " | "\t" | NEWLINE }
+"#;
+
+    fn run<'a>(db: &'a DynamicDB<'a>, input: &str) -> String {
+        let mut out = vec![];
+        Repl::new(db, &mut out).run(input.as_bytes()).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn tells_and_asks() {
+        let db = DynamicDB::from_grammar(GRAMMAR).unwrap();
+        let out = run(
+            &db,
+            "susan likes oranges ◊\nsusan\n  likes apples ◊\nsusan oranges ◊\n\
+             ?susan likes X1 ◊\n?john likes X1 ◊\n:quit\nsusan likes pears ◊\n",
+        );
+        assert!(out.contains("1 added, 0 present, 0 replaced, 0 skipped"));
+        assert!(out.contains("   ...> "));
+        assert!(out.contains("0 added, 0 present, 0 replaced, 1 skipped"));
+        assert!(out.contains("X1 = oranges"));
+        assert!(out.contains("X1 = apples"));
+        assert!(out.contains("uindex> no\n"));
+        assert_eq!(db.len(), 2);
+    }
+
+    #[test]
+    fn runs_commands() {
+        let db = DynamicDB::from_grammar(GRAMMAR).unwrap();
+        let out = run(
+            &db,
+            "susan likes oranges ◊\n:stats\n:explain susan likes X1 ◊\n:explain\n:frobnicate\n",
+        );
+        assert!(out.contains("1 sentences\n  fact: 1\n"));
+        assert!(out.contains("susan likes X1 ◊  (1 in the db)"));
+        assert!(out.contains("    var X1  (variable)"));
+        assert!(out.contains("1 answers"));
+        assert!(out.contains(":explain needs a query"));
+        assert!(out.contains("unknown command :frobnicate, try :help"));
+    }
+
+    #[test]
+    fn saves_and_loads() {
+        let db = DynamicDB::from_grammar(GRAMMAR).unwrap();
+        let path = std::env::temp_dir().join(format!("uindex-repl-{}.kb", std::process::id()));
+        let out = run(
+            &db,
+            &format!("susan likes oranges ◊\n:save {}\n", path.display()),
+        );
+        assert!(out.contains("saved to"));
+        let loaded = DynamicDB::from_grammar(GRAMMAR).unwrap();
+        let out = run(&loaded, &format!(":load {}\n", path.display()));
+        std::fs::remove_file(&path).unwrap();
+        assert!(out.contains("1 added"));
+        assert_eq!(loaded.ask("susan likes oranges ◊").len(), 1);
+    }
+}
@@ -0,0 +1,379 @@
+// Copyright (c) 2020 by Enrique Pérez Arnaud <enrique at cazalla.net>
+//
+// This file is part of the modus_ponens project.
+// http://www.modus_ponens.net
+//
+// The modus_ponens project is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// The modus_ponens project is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with any part of the modus_ponens project.
+// If not, see <http://www.gnu.org/licenses/>.
+
+//! A front end for a db that speaks the Redis protocol (RESP), so that
+//! `redis-cli` and Redis client libraries can be used with it.
+//!
+//! - `UTELL knowledge` tells the sentences, if they can all be parsed, and
+//!   replies with an array with the id and the status of each sentence.
+//! - `UASK query` replies with an array with an array for each answer, with
+//!   the variables in the query and their values, one after the other.
+//! - `UFORGET id` forgets the sentence with the id, and replies 1, or 0 if there
+//!   was none.
+//! - `UGET id` replies with the sentence with the id, or nil.
+//!
+//! Each connection is read in its own thread, but the commands are run one at
+//! a time in the thread that serves the db, since it cannot be shared.
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+
+use log::warn;
+
+use uindex::dynamic::DynamicDB;
+use uindex::kbase::{DataBase, FactId, Told};
+
+/// The largest bulk string accepted, as in Redis.
+const MAX_BULK_LENGTH: usize = 512 * 1024 * 1024;
+
+enum Reply {
+    Simple(&'static str),
+    Error(String),
+    Integer(i64),
+    Bulk(Option<String>),
+    Array(Vec<Reply>),
+}
+
+type Command = (Vec<String>, Sender<Reply>);
+
+/// Serves the db on the listener, until it fails.
+pub fn serve<'a>(db: &'a DynamicDB<'a>, listener: TcpListener) -> io::Result<()> {
+    let (commands, received) = mpsc::channel::<Command>();
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    let commands = commands.clone();
+                    thread::spawn(move || {
+                        if let Err(error) = connection(&stream, commands) {
+                            warn!("Failed to serve {:?}: {}", stream.peer_addr(), error);
+                        }
+                    });
+                }
+                Err(error) => warn!("Failed to accept a connection: {}", error),
+            }
+        }
+    });
+    for (command, reply) in received {
+        // the connection may have been closed in the meantime
+        let _ = reply.send(run(db, &command));
+    }
+    Ok(())
+}
+
+fn connection(stream: &TcpStream, commands: Sender<Command>) -> io::Result<()> {
+    let mut reader = BufReader::new(stream);
+    let mut writer = io::BufWriter::new(stream);
+    while let Some(command) = read_command(&mut reader)? {
+        let name = command.first().map(|name| name.to_uppercase());
+        let reply = match name.as_deref() {
+            None => continue,
+            Some("QUIT") => {
+                write_reply(&mut writer, &Reply::Simple("OK"))?;
+                return writer.flush();
+            }
+            Some("PING") => Reply::Simple("PONG"),
+            Some("ECHO") if command.len() == 2 => Reply::Bulk(Some(command[1].clone())),
+            Some("COMMAND") => Reply::Array(vec![]),
+            Some(_) => {
+                let (reply, received) = mpsc::channel();
+                if commands.send((command, reply)).is_err() {
+                    return Ok(());
+                }
+                match received.recv() {
+                    Ok(reply) => reply,
+                    Err(_) => return Ok(()),
+                }
+            }
+        };
+        write_reply(&mut writer, &reply)?;
+        writer.flush()?;
+    }
+    Ok(())
+}
+
+fn run<'a>(db: &'a DynamicDB<'a>, command: &[String]) -> Reply {
+    let name = command[0].to_uppercase();
+    match (name.as_str(), &command[1..]) {
+        ("UTELL", [knowledge]) => tell(db, knowledge),
+        ("UASK", [query]) => ask(db, query),
+        ("UFORGET", [id]) => match id.parse() {
+            Ok(id) => Reply::Integer(db.forget(FactId(id)) as i64),
+            Err(_) => invalid_id(),
+        },
+        ("UGET", [id]) => match id.parse() {
+            Ok(id) => Reply::Bulk(db.get(FactId(id))),
+            Err(_) => invalid_id(),
+        },
+        ("UTELL", _) | ("UASK", _) | ("UFORGET", _) | ("UGET", _) | ("ECHO", _) => Reply::Error(
+            format!("ERR wrong number of arguments for '{}' command", command[0]),
+        ),
+        _ => Reply::Error(format!("ERR unknown command '{}'", command[0])),
+    }
+}
+
+fn tell<'a>(db: &'a DynamicDB<'a>, knowledge: &str) -> Reply {
+    if let Some(error) = unparsable(db, knowledge) {
+        return error;
+    }
+    let told = db
+        .tell(knowledge)
+        .into_iter()
+        .map(|Told { id, status }| {
+            let status = format!("{:?}", status).to_lowercase();
+            Reply::Array(vec![
+                Reply::Bulk(Some(id.0.to_string())),
+                Reply::Bulk(Some(status)),
+            ])
+        })
+        .collect();
+    Reply::Array(told)
+}
+
+fn ask<'a>(db: &'a DynamicDB<'a>, query: &str) -> Reply {
+    if let Some(error) = unparsable(db, query) {
+        return error;
+    }
+    let answers = db
+        .ask_bindings(query)
+        .into_iter()
+        .map(|answer| {
+            let values = answer
+                .into_iter()
+                .flat_map(|binding| vec![binding.var, binding.value])
+                .map(|text| Reply::Bulk(Some(text)))
+                .collect();
+            Reply::Array(values)
+        })
+        .collect();
+    Reply::Array(answers)
+}
+
+/// An error for the first sentence in the knowledge that cannot be parsed, if any.
+fn unparsable<'a>(db: &'a DynamicDB<'a>, knowledge: &str) -> Option<Reply> {
+    let skipped = db.check(knowledge).into_iter().next()?;
+    let sentence = skipped.sentence.split_whitespace().collect::<Vec<_>>();
+    Some(Reply::Error(format!(
+        "ERR cannot parse '{}' at line {}, column {}",
+        sentence.join(" "),
+        skipped.line,
+        skipped.column
+    )))
+}
+
+fn invalid_id() -> Reply {
+    Reply::Error(String::from("ERR ids are strings of digits"))
+}
+
+/// Reads a command, either as an array of bulk strings or inline, in which case
+/// the command is the first word, and its argument all that follows it.
+/// Returns `None` when the connection is closed.
+fn read_command<R: BufRead>(reader: &mut R) -> io::Result<Option<Vec<String>>> {
+    let line = match read_line(reader)? {
+        Some(line) => line,
+        None => return Ok(None),
+    };
+    if !line.starts_with('*') {
+        let line = line.trim();
+        let command = match line.find(char::is_whitespace) {
+            Some(end) => vec![line[..end].to_string(), line[end..].trim().to_string()],
+            None if line.is_empty() => vec![],
+            None => vec![line.to_string()],
+        };
+        return Ok(Some(command));
+    }
+    let count = parse_length(&line[1..])?;
+    let mut command = Vec::with_capacity(count.min(16));
+    for _ in 0..count {
+        let header = read_line(reader)?.ok_or_else(|| protocol_error("unexpected end"))?;
+        if !header.starts_with('$') {
+            return Err(protocol_error("expected a bulk string"));
+        }
+        let length = parse_length(&header[1..])?;
+        if length > MAX_BULK_LENGTH {
+            return Err(protocol_error("bulk string too long"));
+        }
+        let mut bytes = vec![0; length + 2];
+        reader.read_exact(&mut bytes)?;
+        bytes.truncate(length);
+        let text = String::from_utf8(bytes).map_err(|_| protocol_error("invalid UTF-8"))?;
+        command.push(text);
+    }
+    Ok(Some(command))
+}
+
+fn read_line<R: BufRead>(reader: &mut R) -> io::Result<Option<String>> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    Ok(Some(line.trim_end_matches(&['\r', '\n'][..]).to_string()))
+}
+
+fn parse_length(text: &str) -> io::Result<usize> {
+    text.parse().map_err(|_| protocol_error("invalid length"))
+}
+
+fn protocol_error(message: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Protocol error: {}", message),
+    )
+}
+
+fn write_reply<W: Write>(writer: &mut W, reply: &Reply) -> io::Result<()> {
+    match reply {
+        Reply::Simple(text) => write!(writer, "+{}\r\n", text),
+        Reply::Error(message) => write!(writer, "-{}\r\n", message),
+        Reply::Integer(number) => write!(writer, ":{}\r\n", number),
+        Reply::Bulk(None) => write!(writer, "$-1\r\n"),
+        Reply::Bulk(Some(text)) => write!(writer, "${}\r\n{}\r\n", text.len(), text),
+        Reply::Array(replies) => {
+            write!(writer, "*{}\r\n", replies.len())?;
+            for reply in replies {
+                write_reply(writer, reply)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::net::SocketAddr;
+
+    const GRAMMAR: &str = r#"
+fact        = { name ~ "likes" ~ name }
+var         = @{ "X" ~ ASCII_DIGIT* }
+v_name      = @{ ASCII_ALPHA+ }
+name        = _{ var | v_name }
+WHITESPACE  = _{ " " | "\t" | NEWLINE }
+"#;
+
+    /// Serves a new db on a free port, and returns its address.
+    fn start() -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            let db: &'static DynamicDB =
+                Box::leak(Box::new(DynamicDB::from_grammar(GRAMMAR).unwrap()));
+            serve(db, listener)
+        });
+        address
+    }
+
+    /// Reads a reply, written as text: simple strings, errors and integers
+    /// as sent, bulk strings as their text or `nil`, and arrays within brackets.
+    fn read_reply<R: BufRead>(reader: &mut R) -> String {
+        let line = read_line(reader).unwrap().unwrap();
+        match &line[..1] {
+            "$" if line == "$-1" => String::from("nil"),
+            "$" => {
+                let mut bytes = vec![0; parse_length(&line[1..]).unwrap() + 2];
+                reader.read_exact(&mut bytes).unwrap();
+                String::from_utf8(bytes).unwrap().trim_end().to_string()
+            }
+            "*" => {
+                let replies: Vec<String> = (0..parse_length(&line[1..]).unwrap())
+                    .map(|_| read_reply(reader))
+                    .collect();
+                format!("[{}]", replies.join(", "))
+            }
+            _ => line,
+        }
+    }
+
+    struct Client {
+        reader: BufReader<TcpStream>,
+        writer: TcpStream,
+    }
+
+    impl Client {
+        fn connect(address: SocketAddr) -> Client {
+            let stream = TcpStream::connect(address).unwrap();
+            Client {
+                reader: BufReader::new(stream.try_clone().unwrap()),
+                writer: stream,
+            }
+        }
+        fn send(&mut self, command: &[&str]) -> String {
+            write!(self.writer, "*{}\r\n", command.len()).unwrap();
+            for part in command {
+                write!(self.writer, "${}\r\n{}\r\n", part.len(), part).unwrap();
+            }
+            read_reply(&mut self.reader)
+        }
+        fn send_inline(&mut self, line: &str) -> String {
+            write!(self.writer, "{}\r\n", line).unwrap();
+            read_reply(&mut self.reader)
+        }
+    }
+
+    #[test]
+    fn serves_the_db() {
+        let mut client = Client::connect(start());
+        assert_eq!(client.send(&["PING"]), "+PONG");
+        let told = client.send(&["UTELL", "susan likes oranges ◊ susan likes apples ◊"]);
+        let id = told[2..told.find(',').unwrap()].to_string();
+        assert_eq!(told.matches(", added]").count(), 2);
+        assert_eq!(
+            client.send(&["UASK", "susan likes X1 ◊"]),
+            "[[X1, oranges], [X1, apples]]"
+        );
+        assert_eq!(client.send(&["UGET", &id]), "susan likes oranges");
+        assert_eq!(client.send_inline(&format!("UFORGET {}", id)), ":1");
+        assert_eq!(client.send(&["UGET", &id]), "nil");
+        assert_eq!(client.send(&["UFORGET", &id]), ":0");
+        assert_eq!(client.send(&["QUIT"]), "+OK");
+    }
+
+    #[test]
+    fn replies_with_errors() {
+        let mut client = Client::connect(start());
+        assert_eq!(
+            client.send(&["UTELL", "susan likes ◊"]),
+            "-ERR cannot parse 'susan likes' at line 1, column 1"
+        );
+        assert_eq!(
+            client.send(&["UGET", "seventeen"]),
+            "-ERR ids are strings of digits"
+        );
+        assert_eq!(
+            client.send(&["UASK"]),
+            "-ERR wrong number of arguments for 'UASK' command"
+        );
+        assert_eq!(
+            client.send(&["FLUSHALL"]),
+            "-ERR unknown command 'FLUSHALL'"
+        );
+    }
+
+    #[test]
+    fn connections_share_the_db() {
+        let address = start();
+        let mut first = Client::connect(address);
+        let mut second = Client::connect(address);
+        first.send(&["UTELL", "john likes pears ◊"]);
+        assert_eq!(second.send(&["UASK", "X1 likes pears ◊"]), "[[X1, john]]");
+    }
+}
@@ -3,7 +3,18 @@ extern crate proc_macro;
 
 use proc_macro::TokenStream;
 
-#[proc_macro_derive(DBGen, attributes(grammar, grammar_inline))]
+#[proc_macro_derive(DBGen, attributes(grammar, grammar_inline, terminator, comment, whitespace, string, var_rule, var_range_prefix, unique_prefix))]
 pub fn derive_gen(input: TokenStream) -> TokenStream {
     uindex::derive_dbase(input.into()).into()
 }
+
+#[proc_macro_derive(Sentence, attributes(sentence, production))]
+pub fn derive_sentence(input: TokenStream) -> TokenStream {
+    uindex::derive_sentence(input.into()).into()
+}
+
+#[doc(hidden)]
+#[proc_macro]
+pub fn __check_sentences(input: TokenStream) -> TokenStream {
+    uindex::check_sentences(input.into()).into()
+}
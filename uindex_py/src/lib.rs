@@ -0,0 +1,162 @@
+// Copyright (c) 2020 by Enrique Pérez Arnaud <enrique at cazalla.net>
+//
+// This file is part of the modus_ponens project.
+// http://www.modus_ponens.net
+//
+// The modus_ponens project is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// The modus_ponens project is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with any part of the modus_ponens project.
+// If not, see <http://www.gnu.org/licenses/>.
+
+//! Python bindings for uindex, with a db for the sentences of a pest grammar
+//! given at runtime:
+//!
+//! ```python
+//! from uindex import DB
+//!
+//! db = DB(open("grammar.pest").read())
+//! db.tell("susan likes apples ◊")
+//! for answer in db.ask("susan likes X1 ◊"):
+//!     print(answer["X1"])
+//! db.save("facts.kb")
+//! ```
+
+// for the code that pyo3 generates for the methods that return a PyResult
+#![allow(clippy::useless_conversion)]
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io;
+
+use pyo3::exceptions::{PyOSError, PyValueError};
+use pyo3::prelude::*;
+
+use uindex::dynamic::{DynamicDB, KnowledgeSyntax};
+use uindex::kbase::{DataBase, FactId, Told};
+use uindex::stream::TellError;
+
+/// A db for the sentences of a pest grammar given at runtime.
+#[pyclass(unsendable)]
+struct DB {
+    // boxed, so that it does not move while the references into it that
+    // it keeps for its sentences are alive
+    db: Box<DynamicDB<'static>>,
+}
+
+impl DB {
+    /// The db, borrowed for as long as the methods of `MPDB` require.
+    /// The results of those methods are owned, so nothing borrowed from
+    /// the db outlives the object.
+    fn db(&self) -> &'static DynamicDB<'static> {
+        unsafe { &*(&*self.db as *const DynamicDB<'static>) }
+    }
+}
+
+#[pymethods]
+impl DB {
+    /// Makes a db for the sentences of the grammar, given as pest source.
+    /// With `strings`, terminators within double quoted strings in knowledge
+    /// do not end sentences. Raises ValueError if the grammar cannot be parsed.
+    #[new]
+    #[pyo3(signature = (grammar, strings = false))]
+    fn new(grammar: &str, strings: bool) -> PyResult<Self> {
+        let db = if strings {
+            DynamicDB::from_grammar_with_syntax(grammar, &KnowledgeSyntax::with_strings())
+        } else {
+            DynamicDB::from_grammar(grammar)
+        };
+        let db = db.map_err(|errors| {
+            let errors: Vec<String> = errors.iter().map(|error| error.to_string()).collect();
+            PyValueError::new_err(errors.join("\n"))
+        })?;
+        Ok(DB { db: Box::new(db) })
+    }
+
+    /// Tells the knowledge, if all its sentences can be parsed, and returns
+    /// a tuple with the id and the status of each sentence, such as "added".
+    /// Raises ValueError with the first sentence that cannot be parsed.
+    fn tell(&self, knowledge: &str) -> PyResult<Vec<(u64, String)>> {
+        check(self.db(), knowledge)?;
+        let told = self
+            .db()
+            .tell(knowledge)
+            .into_iter()
+            .map(|Told { id, status }| (id.0, format!("{:?}", status).to_lowercase()))
+            .collect();
+        Ok(told)
+    }
+
+    /// Returns a dict for each answer to the query, from its variables to their values.
+    /// Raises ValueError with the first sentence that cannot be parsed.
+    fn ask(&self, query: &str) -> PyResult<Vec<HashMap<String, String>>> {
+        check(self.db(), query)?;
+        let answers = self
+            .db()
+            .ask_bindings(query)
+            .into_iter()
+            .map(|answer| answer.into_map())
+            .collect();
+        Ok(answers)
+    }
+
+    /// The sentence with the id, or None if it is not in the db.
+    fn get(&self, id: u64) -> Option<String> {
+        self.db().get(FactId(id))
+    }
+
+    /// Forgets the sentence with the id, returning whether it was in the db.
+    fn forget(&self, id: u64) -> bool {
+        self.db().forget(FactId(id))
+    }
+
+    /// Writes all the sentences in the db to the file at the path,
+    /// so that they can be loaded into another db.
+    fn save(&self, path: &str) -> PyResult<()> {
+        File::create(path)
+            .and_then(|file| self.db().dump(io::BufWriter::new(file)))
+            .map_err(|error| PyOSError::new_err(format!("cannot save to {}: {}", path, error)))
+    }
+
+    /// Tells the knowledge in the file at the path, such as one written by
+    /// `save`, and returns how many sentences were told. Raises OSError if
+    /// the file cannot be read, and ValueError if it cannot be parsed; the
+    /// sentences before the error are told.
+    fn load(&self, path: &str) -> PyResult<usize> {
+        match self.db().tell_file(path) {
+            Ok(progress) => Ok(progress.sentences),
+            Err(error @ TellError::Io { .. }) => Err(PyOSError::new_err(error.to_string())),
+            Err(error) => Err(PyValueError::new_err(error.to_string())),
+        }
+    }
+
+    fn __len__(&self) -> usize {
+        self.db().len()
+    }
+}
+
+/// Fails with the first sentence in the knowledge that cannot be parsed, if any.
+fn check(db: &'static DynamicDB<'static>, knowledge: &str) -> PyResult<()> {
+    match db.check(knowledge).into_iter().next() {
+        Some(skipped) => Err(PyValueError::new_err(format!(
+            "cannot parse the sentence at line {}, column {}:\n{}",
+            skipped.line, skipped.column, skipped.error
+        ))),
+        None => Ok(()),
+    }
+}
+
+#[pymodule]
+#[pyo3(name = "uindex")]
+fn uindex_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<DB>()?;
+    Ok(())
+}
@@ -0,0 +1,86 @@
+"""Smoke tests for the Python bindings, to be run once they are built:
+
+    maturin develop
+    python -m unittest discover tests
+"""
+
+import os
+import tempfile
+import unittest
+
+from uindex import DB
+
+GRAMMAR = r"""
+fact        = { name ~ "likes" ~ name }
+var         = @{ "X" ~ ASCII_DIGIT* }
+v_name      = @{ ASCII_ALPHA+ }
+name        = _{ var | v_name }
+WHITESPACE  = _{ " " | "\t" | NEWLINE }
+"""
+
+QUOTES = r"""
+fact        = { name ~ "says" ~ quote }
+var         = @{ "X" ~ ASCII_DIGIT* }
+v_name      = @{ ASCII_ALPHA+ }
+name        = _{ var | v_name }
+v_quote     = @{ "\"" ~ (!"\"" ~ ANY)* ~ "\"" }
+quote       = _{ var | v_quote }
+WHITESPACE  = _{ " " | "\t" | NEWLINE }
+"""
+
+
+class TestDB(unittest.TestCase):
+    def setUp(self):
+        self.db = DB(GRAMMAR)
+
+    def test_tell_and_ask(self):
+        told = self.db.tell("susan likes apples ◊ susan likes oranges ◊ susan likes apples ◊")
+        self.assertEqual([status for _, status in told], ["added", "added", "present"])
+        self.assertEqual(told[0][0], told[2][0])
+        answers = self.db.ask("susan likes X1 ◊")
+        self.assertEqual(sorted(answer["X1"] for answer in answers), ["apples", "oranges"])
+        self.assertEqual(self.db.ask("john likes X1 ◊"), [])
+        self.assertEqual(len(self.db), 2)
+
+    def test_get_and_forget(self):
+        [(id, _)] = self.db.tell("susan   likes apples ◊")
+        self.assertEqual(self.db.get(id), "susan likes apples")
+        self.assertTrue(self.db.forget(id))
+        self.assertIsNone(self.db.get(id))
+        self.assertFalse(self.db.forget(id))
+        self.assertEqual(len(self.db), 0)
+
+    def test_save_and_load(self):
+        self.db.tell("susan likes apples ◊ john likes pears ◊")
+        with tempfile.TemporaryDirectory() as directory:
+            path = os.path.join(directory, "facts.kb")
+            self.db.save(path)
+            other = DB(GRAMMAR)
+            self.assertEqual(other.load(path), 2)
+        self.assertEqual(len(other), 2)
+        self.assertEqual(len(other.ask("X1 likes X2 ◊")), 2)
+
+    def test_errors(self):
+        with self.assertRaises(ValueError):
+            DB("fact = { ")
+        with self.assertRaises(ValueError):
+            DB('sentence = { var }\nvar = @{ "X" ~ ASCII_DIGIT* }')
+        with self.assertRaises(ValueError):
+            self.db.tell("susan likes ◊")
+        with self.assertRaises(ValueError):
+            self.db.ask("likes ◊")
+        with self.assertRaises(OSError):
+            self.db.load("/nonexistent/facts.kb")
+        self.assertEqual(len(self.db), 0)
+
+    def test_strings(self):
+        db = DB(QUOTES, strings=True)
+        told = db.tell('susan says "see you ◊ soon" ◊ john says "bye" ◊')
+        self.assertEqual(len(told), 2)
+        self.assertEqual(db.ask("susan says X1 ◊"), [{"X1": '"see you ◊ soon"'}])
+        with self.assertRaises(ValueError):
+            DB(QUOTES).tell('susan says "see you ◊ soon" ◊')
+
+
+if __name__ == "__main__":
+    unittest.main()
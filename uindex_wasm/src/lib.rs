@@ -0,0 +1,157 @@
+// Copyright (c) 2020 by Enrique Pérez Arnaud <enrique at cazalla.net>
+//
+// This file is part of the modus_ponens project.
+// http://www.modus_ponens.net
+//
+// The modus_ponens project is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// The modus_ponens project is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with any part of the modus_ponens project.
+// If not, see <http://www.gnu.org/licenses/>.
+
+//! A JavaScript api for uindex, built for `wasm32-unknown-unknown` with
+//! wasm-bindgen, with a db for the sentences of a pest grammar given at runtime:
+//!
+//! ```js
+//! import init, { DB } from "./pkg/uindex_wasm.js";
+//!
+//! await init();
+//! const db = new DB(grammar);
+//! db.tell("susan likes apples ◊");
+//! for (const answer of db.ask("susan likes X1 ◊")) {
+//!     console.log(answer.X1);
+//! }
+//! ```
+//!
+//! Ids are strings, since they do not fit in JavaScript numbers.
+
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use uindex::dynamic::{DynamicDB, KnowledgeSyntax};
+use uindex::kbase::{DataBase, FactId, Told};
+
+/// A db for the sentences of a pest grammar given at runtime.
+#[wasm_bindgen]
+pub struct DB {
+    // boxed, so that it does not move while the references into it that
+    // it keeps for its sentences are alive
+    db: Box<DynamicDB<'static>>,
+}
+
+#[derive(Serialize)]
+struct ToldSentence {
+    id: String,
+    status: String,
+}
+
+impl DB {
+    /// The db, borrowed for as long as the methods of `MPDB` require.
+    /// The results of those methods are owned, so nothing borrowed from
+    /// the db outlives the object.
+    fn db(&self) -> &'static DynamicDB<'static> {
+        unsafe { &*(&*self.db as *const DynamicDB<'static>) }
+    }
+
+    /// The object for a new db, or an error with those in its grammar.
+    fn built<E: ToString>(db: Result<DynamicDB<'static>, Vec<E>>) -> Result<DB, JsError> {
+        let db = db.map_err(|errors| {
+            let errors: Vec<String> = errors.iter().map(|error| error.to_string()).collect();
+            JsError::new(&errors.join("\n"))
+        })?;
+        Ok(DB { db: Box::new(db) })
+    }
+}
+
+#[wasm_bindgen]
+impl DB {
+    /// Makes a db for the sentences of the grammar, given as pest source.
+    /// Throws if the grammar cannot be parsed.
+    #[wasm_bindgen(constructor)]
+    pub fn new(grammar: &str) -> Result<DB, JsError> {
+        DB::built(DynamicDB::from_grammar(grammar))
+    }
+
+    /// Like the constructor, for knowledge in which terminators within
+    /// double quoted strings do not end sentences.
+    #[wasm_bindgen(js_name = withStrings)]
+    pub fn with_strings(grammar: &str) -> Result<DB, JsError> {
+        DB::built(DynamicDB::from_grammar_with_syntax(
+            grammar,
+            &KnowledgeSyntax::with_strings(),
+        ))
+    }
+
+    /// Tells the knowledge, if all its sentences can be parsed, and returns
+    /// `{id, status}` for each sentence, with a status such as "added".
+    /// Throws with the first sentence that cannot be parsed.
+    pub fn tell(&self, knowledge: &str) -> Result<JsValue, JsError> {
+        check(self.db(), knowledge)?;
+        let told: Vec<ToldSentence> = self
+            .db()
+            .tell(knowledge)
+            .into_iter()
+            .map(|Told { id, status }| ToldSentence {
+                id: id.0.to_string(),
+                status: format!("{:?}", status).to_lowercase(),
+            })
+            .collect();
+        to_value(&told)
+    }
+
+    /// Returns an object for each answer to the query, from its variables
+    /// to their values. Throws with the first sentence that cannot be parsed.
+    pub fn ask(&self, query: &str) -> Result<JsValue, JsError> {
+        check(self.db(), query)?;
+        to_value(&self.db().ask_bindings(query))
+    }
+
+    /// The sentence with the id, or undefined if it is not in the db.
+    pub fn get(&self, id: &str) -> Option<String> {
+        self.db().get(FactId(id.parse().ok()?))
+    }
+
+    /// Forgets the sentence with the id, returning whether it was in the db.
+    pub fn forget(&self, id: &str) -> bool {
+        match id.parse() {
+            Ok(id) => self.db().forget(FactId(id)),
+            Err(_) => false,
+        }
+    }
+
+    /// All the sentences in the db, as knowledge that can be told to another db.
+    pub fn dump(&self) -> String {
+        let mut knowledge = vec![];
+        self.db()
+            .dump(&mut knowledge)
+            .expect("writing to memory does not fail");
+        String::from_utf8(knowledge).expect("sentences are valid UTF-8")
+    }
+}
+
+/// Fails with the first sentence in the knowledge that cannot be parsed, if any.
+fn check(db: &'static DynamicDB<'static>, knowledge: &str) -> Result<(), JsError> {
+    match db.check(knowledge).into_iter().next() {
+        Some(skipped) => Err(JsError::new(&format!(
+            "cannot parse the sentence at line {}, column {}:\n{}",
+            skipped.line, skipped.column, skipped.error
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Plain objects, rather than Maps, for the maps from variables to values.
+fn to_value<T: Serialize>(value: &T) -> Result<JsValue, JsError> {
+    let serializer = serde_wasm_bindgen::Serializer::json_compatible();
+    value
+        .serialize(&serializer)
+        .map_err(|error| JsError::new(&error.to_string()))
+}